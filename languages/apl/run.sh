@@ -2,4 +2,4 @@
 set -e
 
 printf %s "$1" > program.apl
-apl --OFF -s -f program.apl || true
+apl --OFF -s -f program.apl
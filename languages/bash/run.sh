@@ -2,4 +2,4 @@
 set -e
 
 printf %s "$1" > program.sh
-bash program.sh || true
+bash program.sh
@@ -1,4 +1,5 @@
 #!/bin/sh
 set -e
 
-printf %s "$1" | bf || true
+printf %s "$1" > program.bf
+bf "$(cat program.bf)"
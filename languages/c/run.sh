@@ -2,4 +2,6 @@
 set -e
 
 printf %s "$1" > program.c
-gcc program.c -o program && ./program || true
+color=${RUNSH_COLOR:+always}
+gcc -fdiagnostics-color="${color:-never}" program.c -o program || exit 125
+./program
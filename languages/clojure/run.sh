@@ -2,4 +2,4 @@
 set -e
 
 printf %s "$1" > program.clj
-clojure program.clj || true
+clojure program.clj
@@ -2,4 +2,6 @@
 set -e
 
 printf %s "$1" > program.cpp
-g++ program.cpp -o program && ./program || true
+color=${RUNSH_COLOR:+always}
+g++ -fdiagnostics-color="${color:-never}" program.cpp -o program || exit 125
+./program
@@ -2,4 +2,5 @@
 set -e
 
 printf %s "$1" > program.cs
-csc program.cs >/dev/null && mono program.exe || true
+csc program.cs >/dev/null || exit 125
+mono program.exe
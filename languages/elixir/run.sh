@@ -2,4 +2,4 @@
 set -e
 
 printf %s "$1" > program.exs
-elixir program.exs || true
+elixir program.exs
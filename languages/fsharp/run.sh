@@ -2,4 +2,5 @@
 set -e
 
 printf %s "$1" > program.fs
-fsharpc --optimize- program.fs >/dev/null && mono program.exe || true
+fsharpc --optimize- program.fs >/dev/null || exit 125
+mono program.exe
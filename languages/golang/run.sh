@@ -3,4 +3,5 @@ set -e
 
 export GOCACHE=/tmp/"$CODEDIR"/cache
 printf %s "$1" > program.go
-go run program.go || true
+go build -o program program.go || exit 125
+./program
@@ -2,4 +2,4 @@
 set -e
 
 printf %s "$1" > program.hs
-ghc -e main program.hs || true
+ghc -e main program.hs
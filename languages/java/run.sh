@@ -2,4 +2,5 @@
 set -e
 
 printf %s "$1" > Main.java
-javac Main.java && java Main || true
+javac Main.java || exit 125
+java Main
@@ -1,4 +1,5 @@
 #!/bin/sh
 set -e
 
-printf %s "$1" | node -p || true
+printf %s "$1" > program.js
+node -p "$(cat program.js)"
@@ -2,4 +2,4 @@
 set -e
 
 printf %s "$1" > program.jl
-julia program.jl || true
+julia program.jl
@@ -2,4 +2,4 @@
 set -e
 
 printf %s "$1" > program.lua
-lua5.3 program.lua || true
+lua5.3 program.lua
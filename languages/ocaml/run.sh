@@ -2,4 +2,5 @@
 set -e
 
 printf %s "$1" > program.ml
-ocamlopt -cclib --static -o program program.ml && ./program || true
+ocamlopt -cclib --static -o program program.ml || exit 125
+./program
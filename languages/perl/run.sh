@@ -2,4 +2,4 @@
 set -e
 
 printf %s "$1" > program.pl
-perl program.pl || true
+perl program.pl
@@ -2,4 +2,4 @@
 set -e
 
 printf %s "$1" > program.php
-php program.php || true
+php program.php
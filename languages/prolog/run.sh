@@ -2,4 +2,4 @@
 set -e
 
 printf %s "$1" > program.pl
-swipl --quiet program.pl || true
+swipl --quiet program.pl
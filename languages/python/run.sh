@@ -2,4 +2,4 @@
 set -e
 
 printf %s "$1" > program.py
-python program.py || true
+python program.py
@@ -2,4 +2,4 @@
 set -e
 
 printf %s "$1" > program.rkt
-racket program.rkt || true
+racket program.rkt
@@ -2,4 +2,4 @@
 set -e
 
 printf %s "$1" > program.rb
-ruby program.rb || true
+ruby program.rb
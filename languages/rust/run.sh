@@ -2,4 +2,6 @@
 set -e
 
 printf %s "$1" > program.rs
-rustc -C opt-level=0 --color never program.rs && ./program || true
+color=${RUNSH_COLOR:+always}
+rustc -C opt-level=0 --color "${color:-never}" program.rs || exit 125
+./program
@@ -2,4 +2,4 @@
 set -e
 
 printf %s "$1" > program.sql
-sqlite3 :memory: < program.sql || true
+sqlite3 :memory: < program.sql
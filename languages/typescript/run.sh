@@ -1,4 +1,5 @@
 #!/bin/sh
 set -e
 
-printf %s "$1" | tsx -p || true
+printf %s "$1" > program.ts
+tsx -p "$(cat program.ts)"
@@ -0,0 +1 @@
+alter table execution drop column if exists verdict;
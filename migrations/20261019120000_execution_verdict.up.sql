@@ -0,0 +1 @@
+alter table execution add column if not exists verdict text;
@@ -0,0 +1,2 @@
+alter table execution drop column if exists code;
+alter table execution drop column if exists author_id;
@@ -0,0 +1,2 @@
+alter table execution add column if not exists author_id text;
+alter table execution add column if not exists code text;
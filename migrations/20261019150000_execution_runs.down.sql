@@ -0,0 +1,2 @@
+alter table execution drop column if exists last_run_at;
+alter table execution drop column if exists runs;
@@ -0,0 +1,6 @@
+alter table execution add column if not exists runs integer not null default 1;
+-- existing executions last ran when they were created, not when this migration ran
+alter table execution add column if not exists last_run_at timestamptz;
+update execution set last_run_at = created_at where last_run_at is null;
+alter table execution alter column last_run_at set default now();
+alter table execution alter column last_run_at set not null;
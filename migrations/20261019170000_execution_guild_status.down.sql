@@ -0,0 +1,4 @@
+drop index if exists execution_author_id_last_run_at_index;
+
+alter table execution drop column if exists status;
+alter table execution drop column if exists guild_id;
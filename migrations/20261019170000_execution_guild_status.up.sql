@@ -0,0 +1,4 @@
+alter table execution add column if not exists guild_id text;
+alter table execution add column if not exists status text;
+
+create index if not exists execution_author_id_last_run_at_index on execution (author_id, last_run_at desc);
@@ -0,0 +1,3 @@
+drop index if exists execution_last_run_at_index;
+
+alter table execution drop column if exists duration_ms;
@@ -0,0 +1,4 @@
+alter table execution add column if not exists duration_ms integer;
+
+-- covers the statistics of a period, so they're computed from the index alone
+create index if not exists execution_last_run_at_index on execution (last_run_at) include (language, status, duration_ms, guild_id, runs);
@@ -0,0 +1,10 @@
+create table if not exists guild_settings (
+	guild_id text primary key,
+	prefix text not null default '$>',
+	languages text[],
+	allowed_channels text[] not null default '{}',
+	forbidden_channels text[] not null default '{}',
+	rerun_on_edit boolean not null default true,
+	split_output boolean not null default false,
+	updated_at timestamptz not null default now()
+);
@@ -0,0 +1,10 @@
+create table if not exists blocklist (
+	id bigserial primary key,
+	kind text not null,
+	target_id text not null,
+	reason text not null,
+	expires_at timestamptz,
+	created_by text not null,
+	created_at timestamptz not null default now(),
+	unique (kind, target_id)
+);
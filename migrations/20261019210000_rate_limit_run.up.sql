@@ -0,0 +1,10 @@
+-- every execution that took a rate limit token, so the limits survive a restart
+create table if not exists rate_limit_run (
+	id bigserial primary key,
+	user_id text,
+	guild_id text,
+	ran_at timestamptz not null default now()
+);
+
+create index if not exists rate_limit_run_user_id_index on rate_limit_run (user_id, ran_at);
+create index if not exists rate_limit_run_guild_id_index on rate_limit_run (guild_id, ran_at);
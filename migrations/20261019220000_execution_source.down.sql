@@ -0,0 +1,2 @@
+alter table execution drop column if exists stdin;
+alter table execution drop column if exists source;
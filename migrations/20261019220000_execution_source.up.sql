@@ -0,0 +1,3 @@
+-- code given to /run, which has no message whose content `code` could hold
+alter table execution add column if not exists source text;
+alter table execution add column if not exists stdin text;
@@ -0,0 +1,2 @@
+update execution set channel_id = '' where channel_id is null;
+alter table execution alter column channel_id set not null;
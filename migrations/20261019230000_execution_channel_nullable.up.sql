@@ -0,0 +1,3 @@
+-- /run can be used where the interaction doesn't say which channel it's in
+alter table execution alter column channel_id drop not null;
+update execution set channel_id = null where channel_id = '';
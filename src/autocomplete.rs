@@ -0,0 +1,137 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use sqlx::PgPool;
+use tokio::sync::Mutex;
+use twilight_model::{
+    application::{
+        command::{CommandOptionChoice, CommandOptionChoiceValue},
+        interaction::{
+            application_command::{CommandData, CommandOptionValue},
+            Interaction,
+        },
+    },
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+};
+
+use crate::{
+    components::respond,
+    hypervisor::languages::{Languages, LANGUAGES},
+    BotFramework,
+};
+
+/// The most choices Discord shows.
+const MAX_CHOICES: usize = 25;
+/// How long the usage counts used for ranking languages are reused.
+const USAGE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Responds to an autocomplete interaction with suggestions for its focused option.
+pub async fn handle(
+    framework: BotFramework,
+    interaction: &Interaction,
+    data: &CommandData,
+) -> anyhow::Result<()> {
+    let focused = data.options.iter().find_map(|option| match &option.value {
+        CommandOptionValue::Focused(input, _) => Some((option.name.as_str(), input.as_str())),
+        _ => None,
+    });
+
+    let choices = match focused {
+        Some(("language", input)) => language_choices(&framework.data.db, input).await?,
+        _ => vec![],
+    };
+
+    respond(
+        &framework,
+        interaction,
+        &InteractionResponse {
+            kind: InteractionResponseType::ApplicationCommandAutocompleteResult,
+            data: Some(InteractionResponseData {
+                choices: Some(choices),
+                ..Default::default()
+            }),
+        },
+    )
+    .await
+}
+
+/// The enabled languages whose name or an alias matches `input`.
+///
+/// Languages starting with `input` come before those only containing it, and more used
+/// languages before less used ones.
+pub async fn language_choices(
+    db: &PgPool,
+    input: &str,
+) -> anyhow::Result<Vec<CommandOptionChoice>> {
+    let usage = language_usage(db).await?;
+    let input = input.trim().to_lowercase();
+
+    let mut matches = LANGUAGES
+        .iter()
+        .filter(|language| language.enabled())
+        .filter_map(|language| {
+            let name = language.to_string();
+            let names = || std::iter::once(name.as_str()).chain(language.aliases().iter().copied());
+            let prefix = names().any(|name| name.starts_with(&input));
+            if !prefix && !names().any(|name| name.contains(&input)) {
+                return None;
+            }
+
+            let uses = usage.get(&name).copied().unwrap_or_default();
+            Some((!prefix, Reverse(uses), name, language))
+        })
+        .collect::<Vec<_>>();
+    matches.sort_by(|a, b| (a.0, a.1, &a.2).cmp(&(b.0, b.1, &b.2)));
+
+    Ok(matches
+        .into_iter()
+        .take(MAX_CHOICES)
+        .map(|(_, _, name, language)| choice(name, language))
+        .collect())
+}
+
+fn choice(name: String, language: &Languages) -> CommandOptionChoice {
+    let aliases = language
+        .aliases()
+        .iter()
+        .filter(|alias| **alias != name)
+        .copied()
+        .collect::<Vec<_>>();
+    let label = if aliases.is_empty() {
+        name.clone()
+    } else {
+        format!("{name} ({})", aliases.join(", "))
+    };
+
+    CommandOptionChoice {
+        name: label,
+        name_localizations: None,
+        value: CommandOptionChoiceValue::String(name),
+    }
+}
+
+/// How many executions each language had, cached for [`USAGE_TTL`].
+async fn language_usage(db: &PgPool) -> anyhow::Result<HashMap<String, i64>> {
+    static USAGE: Lazy<Mutex<Option<(Instant, HashMap<String, i64>)>>> =
+        Lazy::new(Default::default);
+
+    let mut usage = USAGE.lock().await;
+    if let Some((fetched, counts)) = usage.as_ref() {
+        if fetched.elapsed() < USAGE_TTL {
+            return Ok(counts.clone());
+        }
+    }
+
+    let counts =
+        sqlx::query!(r#"select language, count(*) as "count!" from execution group by language"#)
+            .fetch_all(db)
+            .await?
+            .into_iter()
+            .map(|row| (row.language, row.count))
+            .collect::<HashMap<_, _>>();
+    *usage = Some((Instant::now(), counts.clone()));
+
+    Ok(counts)
+}
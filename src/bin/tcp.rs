@@ -5,11 +5,13 @@ use dotenvy::dotenv;
 use futures::{SinkExt, StreamExt};
 use run_sh::{
     config::CONFIG,
-    hypervisor::{languages::Languages, Hypervisor},
+    hypervisor::{languages::Languages, Hypervisor, RunOptions},
+    render,
 };
 use std::net::SocketAddr;
 use tokio::net::{TcpListener, TcpStream};
 use tokio_util::codec::{Framed, LinesCodec};
+use tokio_util::sync::CancellationToken;
 use tracing_subscriber::{fmt, prelude::*, EnvFilter, Registry};
 
 #[tokio::main]
@@ -110,25 +112,42 @@ async fn handle_connection(hypervisor: Arc<Hypervisor>, socket: TcpStream, addr:
                     .await
                     .expect("failed to write data to socket");
                 // execute code
-                let res = hypervisor.exec(&language, code).await;
-                match res {
-                    Ok(output) => {
-                        let out = output
-                            .iter()
-                            .map(|b| String::from_utf8_lossy(b))
-                            .collect::<Vec<_>>()
-                            .join("\n");
-                        lines
-                            .send(out)
-                            .await
-                            .expect("failed to write data to socket");
-                    }
+                let mut stream = match hypervisor
+                    .exec_stream(
+                        &language,
+                        code,
+                        RunOptions::default(),
+                        &CancellationToken::new(),
+                    )
+                    .await
+                {
+                    Ok(stream) => stream,
                     Err(e) => {
                         lines
                             .send(format!("Error: {e:#?}"))
                             .await
                             .expect("failed to write data to socket");
+                        continue;
                     }
+                };
+                while let Some(chunk) = stream.next().await {
+                    let out = match chunk {
+                        Ok(chunk) => render::output_text(&[chunk]).trim_end().to_string(),
+                        Err(e) => format!("Error: {e:#?}"),
+                    };
+                    lines
+                        .send(out)
+                        .await
+                        .expect("failed to write data to socket");
+                }
+                if let Some(finished) = stream.finished().await {
+                    let status = finished
+                        .status
+                        .map_or_else(|| "finished".to_string(), |status| status.to_string());
+                    lines
+                        .send(format!("({status} • {})", finished.usage))
+                        .await
+                        .expect("failed to write data to socket");
                 }
             }
             // An error occurred.
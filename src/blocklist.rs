@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use sqlx::{types::time::OffsetDateTime, PgPool};
+use twilight_model::id::{
+    marker::{ChannelMarker, GuildMarker, UserMarker},
+    Id,
+};
+
+use crate::config::CONFIG;
+
+/// What a blocklist entry blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    User,
+    Guild,
+    Channel,
+}
+
+impl Kind {
+    /// Reads a kind stored in the `blocklist` table.
+    pub fn parse(kind: &str) -> Option<Self> {
+        let kind = match kind {
+            "user" => Self::User,
+            "guild" => Self::Guild,
+            "channel" => Self::Channel,
+            _ => return None,
+        };
+
+        Some(kind)
+    }
+
+    /// The name of the kind as stored in the `blocklist` table.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Guild => "guild",
+            Self::Channel => "channel",
+        }
+    }
+}
+
+/// Why and until when something is blocked.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub reason: String,
+    /// When the entry expires, or `None` if it doesn't
+    pub expires_at: Option<OffsetDateTime>,
+}
+
+impl Entry {
+    fn active(&self) -> bool {
+        match self.expires_at {
+            Some(expires_at) => expires_at > OffsetDateTime::now_utc(),
+            None => true,
+        }
+    }
+
+    /// The short notice blocked users get if `BLOCK_NOTICE` is set.
+    pub fn notice(&self) -> String {
+        format!("-# 🚫 You can't run code here: {}", self.reason)
+    }
+}
+
+/// The users, guilds and channels the bot ignores, kept in memory and persisted in the
+/// `blocklist` table.
+#[derive(Default)]
+pub struct Blocklist {
+    entries: Mutex<HashMap<(Kind, String), Entry>>,
+}
+
+impl Blocklist {
+    /// Loads the entries that haven't expired yet.
+    pub async fn load(db: &PgPool) -> anyhow::Result<Self> {
+        let rows = sqlx::query!(
+            "select kind, target_id, reason, expires_at from blocklist where expires_at is null or expires_at > now()"
+        )
+        .fetch_all(db)
+        .await?;
+
+        let entries = rows
+            .into_iter()
+            .filter_map(|row| {
+                let kind = Kind::parse(&row.kind)?;
+                let entry = Entry {
+                    reason: row.reason,
+                    expires_at: row.expires_at,
+                };
+
+                Some(((kind, row.target_id), entry))
+            })
+            .collect();
+
+        Ok(Self {
+            entries: Mutex::new(entries),
+        })
+    }
+
+    /// The entry blocking `user`, `guild` or `channel`, if any. Owners are never blocked.
+    pub fn check(
+        &self,
+        user: Option<Id<UserMarker>>,
+        guild: Option<Id<GuildMarker>>,
+        channel: Option<Id<ChannelMarker>>,
+    ) -> Option<Entry> {
+        if user.is_some_and(|user| CONFIG.owner_ids.contains(&user)) {
+            return None;
+        }
+
+        let targets = [
+            user.map(|user| (Kind::User, user.to_string())),
+            guild.map(|guild| (Kind::Guild, guild.to_string())),
+            channel.map(|channel| (Kind::Channel, channel.to_string())),
+        ];
+        let entries = self.entries.lock().unwrap();
+        targets
+            .into_iter()
+            .flatten()
+            .filter_map(|target| entries.get(&target))
+            .find(|entry| entry.active())
+            .cloned()
+    }
+
+    /// Blocks `target`, replacing the entry it already had.
+    pub async fn add(
+        &self,
+        db: &PgPool,
+        kind: Kind,
+        target: String,
+        entry: Entry,
+        created_by: Id<UserMarker>,
+    ) -> anyhow::Result<()> {
+        let created_by = created_by.to_string();
+        sqlx::query!(
+            "insert into blocklist (kind, target_id, reason, expires_at, created_by) values ($1, $2, $3, $4, $5)
+            on conflict (kind, target_id) do update set reason = excluded.reason, expires_at = excluded.expires_at, created_by = excluded.created_by, created_at = now()",
+            kind.as_str(),
+            target,
+            entry.reason,
+            entry.expires_at,
+            created_by
+        )
+        .execute(db)
+        .await?;
+        self.entries.lock().unwrap().insert((kind, target), entry);
+
+        Ok(())
+    }
+
+    /// Unblocks `target`.
+    ///
+    /// Returns `false` if it wasn't blocked.
+    pub async fn remove(&self, db: &PgPool, kind: Kind, target: String) -> anyhow::Result<bool> {
+        let removed = sqlx::query!(
+            "delete from blocklist where kind = $1 and target_id = $2",
+            kind.as_str(),
+            target
+        )
+        .execute(db)
+        .await?
+        .rows_affected()
+            > 0;
+        self.entries.lock().unwrap().remove(&(kind, target));
+
+        Ok(removed)
+    }
+}
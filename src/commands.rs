@@ -1,13 +1,32 @@
 use twilight_model::{
-    channel::message::MessageFlags,
-    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    application::{
+        command::{Command, CommandOption},
+        interaction::InteractionContextType,
+    },
+    channel::message::{Component, MessageFlags},
+    guild::Permissions,
+    http::{
+        attachment::Attachment,
+        interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    },
+    oauth::ApplicationIntegrationType,
 };
 use vesper::prelude::*;
 
-use crate::state::BotState;
+use crate::{config::CONFIG, metrics, state::BotState, BotFramework};
 
+pub mod block;
+pub mod check_code;
 pub mod execute_code;
+pub mod format_code;
+pub mod history;
 pub mod languages;
+pub mod run;
+pub mod settings;
+pub mod stats;
+
+/// The commands only owners can use, which nobody else needs to see.
+const OWNER_COMMANDS: &[&str] = &["block", "unblock"];
 
 pub async fn text_response(
     ctx: &SlashContext<'_, BotState>,
@@ -31,12 +50,16 @@ pub async fn text_response(
                 }),
             },
         )
-        .await?;
+        .await
+        .inspect_err(metrics::record_http_error)?;
 
     Ok(())
 }
 
-pub async fn defer_response(ctx: &SlashContext<'_, BotState>) -> anyhow::Result<()> {
+pub async fn defer_response(
+    ctx: &SlashContext<'_, BotState>,
+    ephemeral: bool,
+) -> anyhow::Result<()> {
     ctx.interaction_client
         .create_response(
             ctx.interaction.id,
@@ -45,21 +68,183 @@ pub async fn defer_response(ctx: &SlashContext<'_, BotState>) -> anyhow::Result<
                 kind: InteractionResponseType::DeferredChannelMessageWithSource,
                 data: Some(InteractionResponseData {
                     content: None,
-                    flags: None,
+                    flags: if ephemeral {
+                        Some(MessageFlags::EPHEMERAL)
+                    } else {
+                        None
+                    },
                     ..Default::default()
                 }),
             },
         )
-        .await?;
+        .await
+        .inspect_err(metrics::record_http_error)?;
+
+    Ok(())
+}
+
+pub async fn edit_response(
+    ctx: &SlashContext<'_, BotState>,
+    text: String,
+    components: &[Component],
+) -> DefaultCommandResult {
+    ctx.interaction_client
+        .update_response(&ctx.interaction.token)
+        .content(Some(&text))
+        .components(Some(components))
+        .await
+        .inspect_err(metrics::record_http_error)?;
 
     Ok(())
 }
 
-pub async fn edit_response(ctx: &SlashContext<'_, BotState>, text: String) -> DefaultCommandResult {
+/// Edits the response, replacing its attachments with `attachments`.
+pub async fn edit_response_with_attachments(
+    ctx: &SlashContext<'_, BotState>,
+    text: String,
+    components: &[Component],
+    attachments: &[Attachment],
+) -> DefaultCommandResult {
     ctx.interaction_client
         .update_response(&ctx.interaction.token)
         .content(Some(&text))
-        .await?;
+        .components(Some(components))
+        .attachments(attachments)
+        .keep_attachment_ids(&[])
+        .await
+        .inspect_err(metrics::record_http_error)?;
 
     Ok(())
 }
+
+/// Whether the user who ran the command is an owner of the bot.
+pub fn is_owner(ctx: &SlashContext<'_, BotState>) -> bool {
+    ctx.interaction
+        .author_id()
+        .is_some_and(|user| CONFIG.owner_ids.contains(&user))
+}
+
+/// Registers the commands of the framework with Discord, in the development guild if one is
+/// configured and globally otherwise. Nothing is sent if they are registered already.
+pub async fn register(framework: &BotFramework) -> anyhow::Result<()> {
+    let client = framework
+        .http_client()
+        .interaction(CONFIG.discord_application_id);
+    let commands = framework
+        .twilight_commands()
+        .into_iter()
+        .map(prepare)
+        .collect::<Vec<_>>();
+
+    let registered = match CONFIG.dev_guild_id {
+        Some(guild_id) => client.guild_commands(guild_id).await?.models().await?,
+        None => client.global_commands().await?.models().await?,
+    };
+    if log_changes(&registered, &commands) == 0 {
+        tracing::info!("{} commands are registered already", commands.len());
+        return Ok(());
+    }
+
+    match CONFIG.dev_guild_id {
+        Some(guild_id) => {
+            client.set_guild_commands(guild_id, &commands).await?;
+            tracing::info!("registered {} commands in guild {guild_id}", commands.len());
+        }
+        None => {
+            client.set_global_commands(&commands).await?;
+            tracing::info!("registered {} commands globally", commands.len());
+        }
+    }
+
+    Ok(())
+}
+
+/// Makes a command usable wherever the bot is installed, for guilds and for users alike, unless
+/// it's only for guilds.
+///
+/// Owner commands are hidden from everyone but administrators of the guilds the bot is in.
+fn prepare(mut command: Command) -> Command {
+    if OWNER_COMMANDS.contains(&command.name.as_str()) {
+        command.default_member_permissions = Some(Permissions::empty());
+        command.dm_permission = Some(false);
+    }
+
+    if command.dm_permission == Some(false) {
+        command.contexts = Some(vec![InteractionContextType::Guild]);
+        command.integration_types = Some(vec![ApplicationIntegrationType::GuildInstall]);
+    } else {
+        command.contexts = Some(vec![
+            InteractionContextType::Guild,
+            InteractionContextType::BotDm,
+            InteractionContextType::PrivateChannel,
+        ]);
+        command.integration_types = Some(vec![
+            ApplicationIntegrationType::GuildInstall,
+            ApplicationIntegrationType::UserInstall,
+        ]);
+    }
+    for option in &mut command.options {
+        // languages are suggested by `autocomplete`
+        if option.name == "language" {
+            option.autocomplete = Some(true);
+        }
+    }
+
+    command
+}
+
+/// Logs how `commands` differ from the `registered` ones, returning the number of changes.
+fn log_changes(registered: &[Command], commands: &[Command]) -> usize {
+    let mut changes = 0;
+
+    for command in commands {
+        match find(registered, command) {
+            None => tracing::info!("adding command `{}`", command.name),
+            Some(current) if !same(current, command) => {
+                tracing::info!("updating command `{}`", command.name)
+            }
+            Some(_) => continue,
+        }
+        changes += 1;
+    }
+    for command in registered {
+        if find(commands, command).is_none() {
+            tracing::info!("removing command `{}`", command.name);
+            changes += 1;
+        }
+    }
+
+    changes
+}
+
+/// Finds the command with the name and kind of `command`.
+fn find<'a>(commands: &'a [Command], command: &Command) -> Option<&'a Command> {
+    commands
+        .iter()
+        .find(|other| other.name == command.name && other.kind == command.kind)
+}
+
+/// Whether two commands are defined the same, ignoring what Discord assigns to them.
+fn same(a: &Command, b: &Command) -> bool {
+    a.description == b.description
+        && a.default_member_permissions == b.default_member_permissions
+        && a.nsfw.unwrap_or_default() == b.nsfw.unwrap_or_default()
+        && a.contexts == b.contexts
+        && a.integration_types == b.integration_types
+        && a.options.len() == b.options.len()
+        && a.options
+            .iter()
+            .zip(&b.options)
+            .all(|(a, b)| same_option(a, b))
+}
+
+/// Whether two options are defined the same, treating unset flags as false.
+fn same_option(a: &CommandOption, b: &CommandOption) -> bool {
+    a.name == b.name
+        && a.kind == b.kind
+        && a.description == b.description
+        && a.required.unwrap_or_default() == b.required.unwrap_or_default()
+        && a.autocomplete.unwrap_or_default() == b.autocomplete.unwrap_or_default()
+        && a.choices == b.choices
+        && a.options == b.options
+}
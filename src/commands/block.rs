@@ -0,0 +1,122 @@
+use sqlx::types::time::{Duration, OffsetDateTime};
+use twilight_model::id::{marker::GenericMarker, Id};
+use vesper::prelude::*;
+
+use crate::{
+    blocklist::{Entry, Kind},
+    state::BotState,
+};
+
+use super::{is_owner, text_response};
+
+/// The longest a block can last before it's permanent, a year.
+const MAX_BLOCK_HOURS: i64 = 24 * 365;
+
+#[derive(Parse, Clone, Copy)]
+pub enum Target {
+    #[parse(rename = "User")]
+    User,
+    #[parse(rename = "Server")]
+    Guild,
+    #[parse(rename = "Channel")]
+    Channel,
+}
+
+impl From<Target> for Kind {
+    fn from(target: Target) -> Self {
+        match target {
+            Target::User => Kind::User,
+            Target::Guild => Kind::Guild,
+            Target::Channel => Kind::Channel,
+        }
+    }
+}
+
+#[command(name = "block")]
+#[description = "Stops a user, server or channel from running code (owners only)"]
+pub async fn block(
+    ctx: &SlashContext<'_, BotState>,
+    #[description = "What to block"] target: Target,
+    #[description = "The id of what to block"] id: String,
+    #[description = "Why it's blocked"] reason: String,
+    #[description = "How many hours the block lasts, forever by default"] hours: Option<i64>,
+) -> DefaultCommandResult {
+    if !is_owner(ctx) {
+        return text_response(ctx, "Only owners can block.".to_string(), true).await;
+    }
+    let Ok(id) = id.trim().parse::<Id<GenericMarker>>() else {
+        return text_response(ctx, format!("`{id}` is not an id."), true).await;
+    };
+    let expires_at = match hours {
+        Some(hours @ 1..=MAX_BLOCK_HOURS) => {
+            Some(OffsetDateTime::now_utc() + Duration::hours(hours))
+        }
+        Some(_) => {
+            return text_response(
+                ctx,
+                format!("The block must last 1 to {MAX_BLOCK_HOURS} hours, or forever."),
+                true,
+            )
+            .await
+        }
+        None => None,
+    };
+    let Some(owner) = ctx.interaction.author_id() else {
+        return Ok(());
+    };
+
+    let kind = Kind::from(target);
+    ctx.data
+        .blocklist
+        .add(
+            &ctx.data.db,
+            kind,
+            id.to_string(),
+            Entry { reason, expires_at },
+            owner,
+        )
+        .await?;
+
+    let until = match expires_at {
+        Some(expires_at) => format!("until <t:{}:f>", expires_at.unix_timestamp()),
+        None => "until unblocked".to_string(),
+    };
+    text_response(
+        ctx,
+        format!("Blocked {} `{id}` {until}.", kind.as_str()),
+        true,
+    )
+    .await
+}
+
+#[command(name = "unblock")]
+#[description = "Lets a blocked user, server or channel run code again (owners only)"]
+pub async fn unblock(
+    ctx: &SlashContext<'_, BotState>,
+    #[description = "What to unblock"] target: Target,
+    #[description = "The id of what to unblock"] id: String,
+) -> DefaultCommandResult {
+    if !is_owner(ctx) {
+        return text_response(ctx, "Only owners can unblock.".to_string(), true).await;
+    }
+    let Ok(id) = id.trim().parse::<Id<GenericMarker>>() else {
+        return text_response(ctx, format!("`{id}` is not an id."), true).await;
+    };
+
+    let kind = Kind::from(target);
+    let content = if ctx
+        .data
+        .blocklist
+        .remove(&ctx.data.db, kind, id.to_string())
+        .await?
+    {
+        format!("Unblocked {} `{id}`.", kind.as_str())
+    } else {
+        format!(
+            "Nothing to unblock, {} `{id}` isn't blocked.",
+            kind.as_str()
+        )
+    };
+
+    text_response(ctx, content, true).await
+}
@@ -0,0 +1,75 @@
+use tokio_util::sync::CancellationToken;
+use twilight_model::application::interaction::InteractionData;
+use vesper::prelude::*;
+
+use crate::{execution, hypervisor::check, state::BotState};
+
+use super::{defer_response, edit_response, text_response};
+
+#[command(message, name = "Check Code")]
+#[description = "Check code with the static checker of its language"]
+pub async fn check_code(ctx: &SlashContext<'_, BotState>) -> DefaultCommandResult {
+    let data = match &ctx.interaction.data {
+        Some(InteractionData::ApplicationCommand(data)) => data,
+        _ => return Ok(()),
+    };
+    let Some((_, message)) = data
+        .resolved
+        .as_ref()
+        .and_then(|resolved| resolved.messages.iter().next())
+    else {
+        return Ok(());
+    };
+
+    let Some(code) = crate::parsers::match_code(&message.content, true) else {
+        return text_response(ctx, "Code input could not be parsed.".to_string(), true).await;
+    };
+    let settings = ctx
+        .data
+        .settings
+        .get(&ctx.data.db, ctx.interaction.guild_id)
+        .await?;
+    let language = match execution::language(code.language, &settings) {
+        Ok(language) => language,
+        Err(rejection) => return text_response(ctx, rejection.to_string(), true).await,
+    };
+    if language.checker().is_none() {
+        return text_response(
+            ctx,
+            format!("Checking `{language}` is not supported."),
+            true,
+        )
+        .await;
+    }
+    if let Err(rejection) = execution::acquire(
+        &ctx.data,
+        ctx.interaction.author_id(),
+        ctx.interaction.guild_id,
+    )
+    .await?
+    {
+        return text_response(ctx, rejection.to_string(), true).await;
+    }
+
+    defer_response(ctx, false).await?;
+
+    let content = match ctx
+        .data
+        .hypervisor
+        .check(&language, code.code, &CancellationToken::new())
+        .await
+    {
+        Ok(report) => check::format_report(&report),
+        Err(e) => {
+            tracing::error!("failed to check code - {e:#?}");
+
+            format!("Failed to check code: {e}")
+        }
+    };
+
+    if let Err(e) = edit_response(ctx, content, &[]).await {
+        tracing::error!("failed to reply to interaction - {e}");
+    }
+
+    Ok(())
+}
@@ -1,8 +1,12 @@
+use std::time::Instant;
+
 use twilight_model::application::interaction::InteractionData;
 use vesper::prelude::*;
 
 use crate::{
-    hypervisor::{format_output, languages::Languages},
+    components::{actions_row, stop},
+    execution::{self, Finish},
+    reply::{execute, Reply},
     state::BotState,
 };
 
@@ -27,72 +31,79 @@ pub async fn execute_code(ctx: &SlashContext<'_, BotState>) -> DefaultCommandRes
     let Some(code) = crate::parsers::match_code(&message.content, true) else {
         return text_response(ctx, "Code input could not be parsed.".to_string(), true).await;
     };
-    let Some(language) = Languages::from_codeblock_language(code.language) else {
-        if let Err(e) = text_response(
-            ctx,
-            format!(
-                "Unsupported language `{language}`",
-                language = code.language
-            ),
-            true,
-        )
-        .await
-        {
-            tracing::error!("failed to reply to interaction - {e}");
-        }
-
-        return Ok(());
-    };
-    if !language.enabled() {
-        if let Err(e) = text_response(
-            ctx,
-            format!(
-                "Unsupported language `{language}`",
-                language = code.language
-            ),
-            true,
-        )
-        .await
-        {
-            tracing::error!("failed to reply to message - {e}");
-        }
-
-        return Ok(());
-    }
-
-    defer_response(ctx).await?;
-
-    let code_result = match ctx.data.hypervisor.exec(&language, code.code).await {
-        Ok(res) => res,
-        Err(e) => {
-            tracing::error!("failed to execute code - {e:#?}");
-
-            if let Err(e) = edit_response(ctx, format!("Failed to execute code: {e}")).await {
-                tracing::error!("failed to reply to interaction - {e}");
-            }
-
-            return Ok(());
-        }
+    let settings = ctx
+        .data
+        .settings
+        .get(&ctx.data.db, ctx.interaction.guild_id)
+        .await?;
+    let (language, options) = match execution::prepare(
+        &ctx.data,
+        &code,
+        &settings,
+        ctx.interaction.author_id(),
+        ctx.interaction.guild_id,
+    )
+    .await?
+    {
+        Ok(prepared) => prepared,
+        Err(rejection) => return text_response(ctx, rejection.to_string(), true).await,
     };
 
-    let out = format_output(code_result);
-
-    if let Err(e) = edit_response(ctx, format!("```{language}\n{out}\n```")).await {
-        tracing::error!("failed to reply to interaction - {e}");
-        return Ok(());
-    };
+    defer_response(ctx, false).await?;
 
     let channel_id = message.channel_id.to_string();
     let message_id = message.id.to_string();
-    let language = language.to_string();
-    sqlx::query!(
-        r#"insert into execution (channel_id, message_id, language, reply_id) values ($1, $2, $3, 'interaction') returning *;"#,
+    let language_name = language.to_string();
+    let author_id = ctx.interaction.author_id().map(|user| user.to_string());
+    let guild_id = ctx.interaction.guild_id.map(|guild| guild.to_string());
+    let execution = sqlx::query!(
+        r#"insert into execution (channel_id, message_id, language, reply_id, author_id, code, guild_id) values ($1, $2, $3, 'interaction', $4, $5, $6) returning *;"#,
         channel_id,
         message_id,
-        language,
+        language_name,
+        author_id,
+        message.content,
+        guild_id
     )
     .fetch_one(&ctx.data.db)
     .await?;
 
+    let running = ctx.data.running.start(ctx.interaction.id.cast());
+    let components = ctx
+        .interaction
+        .author_id()
+        .map(|user| vec![stop::row(running.key(), user)])
+        .unwrap_or_default();
+    edit_response(ctx, "-# ⏳ Running your code...".to_string(), &components).await?;
+
+    let reply = Reply::Interaction {
+        client: &ctx.interaction_client,
+        token: &ctx.interaction.token,
+    };
+    let started = Instant::now();
+    let result = execute(
+        &reply,
+        &ctx.data.hypervisor,
+        &language,
+        &code,
+        options,
+        running.token(),
+        &components,
+    )
+    .await;
+    execution::finish(
+        &ctx.data,
+        &reply,
+        &running,
+        result,
+        started.elapsed(),
+        Finish {
+            id: execution.id,
+            actions: actions_row(execution.id),
+            edit_hint: false,
+        },
+    )
+    .await?;
+
     Ok(())
 }
@@ -0,0 +1,89 @@
+use tokio_util::sync::CancellationToken;
+use twilight_model::application::interaction::InteractionData;
+use vesper::prelude::*;
+
+use crate::{execution, render, state::BotState};
+
+use super::{defer_response, edit_response_with_attachments, text_response};
+
+#[command(message, name = "Format Code")]
+#[description = "Format code with the formatter of its language"]
+pub async fn format_code(ctx: &SlashContext<'_, BotState>) -> DefaultCommandResult {
+    let data = match &ctx.interaction.data {
+        Some(InteractionData::ApplicationCommand(data)) => data,
+        _ => return Ok(()),
+    };
+    let Some((_, message)) = data
+        .resolved
+        .as_ref()
+        .and_then(|resolved| resolved.messages.iter().next())
+    else {
+        return Ok(());
+    };
+
+    let Some(code) = crate::parsers::match_code(&message.content, true) else {
+        return text_response(ctx, "Code input could not be parsed.".to_string(), true).await;
+    };
+    let settings = ctx
+        .data
+        .settings
+        .get(&ctx.data.db, ctx.interaction.guild_id)
+        .await?;
+    let language = match execution::language(code.language, &settings) {
+        Ok(language) => language,
+        Err(rejection) => return text_response(ctx, rejection.to_string(), true).await,
+    };
+    if language.format_command().is_none() {
+        return text_response(
+            ctx,
+            format!("Formatting `{language}` is not supported."),
+            true,
+        )
+        .await;
+    }
+    if let Err(rejection) = execution::acquire(
+        &ctx.data,
+        ctx.interaction.author_id(),
+        ctx.interaction.guild_id,
+    )
+    .await?
+    {
+        return text_response(ctx, rejection.to_string(), true).await;
+    }
+
+    defer_response(ctx, false).await?;
+
+    let (content, attachments) = match ctx
+        .data
+        .hypervisor
+        .format(&language, code.code, &CancellationToken::new())
+        .await
+    {
+        Ok(formatted) => {
+            let formatted = String::from_utf8_lossy(&formatted.concat()).into_owned();
+            // cutting formatted code down would break it, so it's attached whole instead
+            if formatted.chars().count() <= render::MAX_INLINE_OUTPUT {
+                (render::code_block(&language, &formatted), vec![])
+            } else {
+                let filename = format!("formatted.{}", language.extension());
+                let lines = formatted.lines().count();
+
+                (
+                    format!("-# 📎 {lines} lines, see the attached file"),
+                    vec![render::attachment(filename, &formatted, 0)],
+                )
+            }
+        }
+        Err(e) => {
+            tracing::error!("failed to format code - {e:#?}");
+
+            (format!("Failed to format code: {e}"), vec![])
+        }
+    };
+
+    if let Err(e) = edit_response_with_attachments(ctx, content, &[], &attachments).await {
+        tracing::error!("failed to reply to interaction - {e}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use twilight_model::http::interaction::{InteractionResponse, InteractionResponseType};
+use vesper::prelude::*;
+
+use crate::{components::history, metrics, state::BotState};
+
+use super::text_response;
+
+#[command(name = "history")]
+#[description = "Lists the code you ran recently"]
+pub async fn history(ctx: &SlashContext<'_, BotState>) -> DefaultCommandResult {
+    let Some(user) = ctx.interaction.author_id() else {
+        return text_response(ctx, "No user to show the history of.".to_string(), true).await;
+    };
+    let data = history::render(&ctx.data.db, user, 0).await?;
+
+    ctx.interaction_client
+        .create_response(
+            ctx.interaction.id,
+            &ctx.interaction.token,
+            &InteractionResponse {
+                kind: InteractionResponseType::ChannelMessageWithSource,
+                data: Some(data),
+            },
+        )
+        .await
+        .inspect_err(metrics::record_http_error)?;
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use twilight_model::{
+    channel::message::component::{ActionRow, Component, TextInput, TextInputStyle},
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+};
+use vesper::prelude::*;
+
+use crate::{execution, metrics, modals, state::BotState};
+
+use super::text_response;
+
+/// The most code a modal text input can hold.
+const MAX_CODE_LENGTH: u16 = 4000;
+
+#[command(name = "run")]
+#[description = "Run code in a sandboxed environment"]
+pub async fn run(
+    ctx: &SlashContext<'_, BotState>,
+    #[description = "The language of the code"] language: String,
+) -> DefaultCommandResult {
+    let settings = ctx
+        .data
+        .settings
+        .get(&ctx.data.db, ctx.interaction.guild_id)
+        .await?;
+    let language = match execution::language(&language, &settings) {
+        Ok(language) => language,
+        Err(rejection) => return text_response(ctx, rejection.to_string(), true).await,
+    };
+
+    let input = |custom_id: &str, label: &str, placeholder: &str, required: bool| {
+        Component::ActionRow(ActionRow {
+            components: vec![Component::TextInput(TextInput {
+                custom_id: custom_id.to_string(),
+                label: label.to_string(),
+                max_length: Some(MAX_CODE_LENGTH),
+                min_length: None,
+                placeholder: Some(placeholder.to_string()),
+                required: Some(required),
+                style: TextInputStyle::Paragraph,
+                value: None,
+            })],
+        })
+    };
+
+    ctx.interaction_client
+        .create_response(
+            ctx.interaction.id,
+            &ctx.interaction.token,
+            &InteractionResponse {
+                kind: InteractionResponseType::Modal,
+                data: Some(InteractionResponseData {
+                    custom_id: Some(format!("{}:{language}", modals::run::ID)),
+                    title: Some(format!("Run {language}")),
+                    components: Some(vec![
+                        input(modals::run::CODE, "Code", "Your code", true),
+                        input(
+                            modals::run::STDIN,
+                            "Input",
+                            "Fed to the program through stdin",
+                            false,
+                        ),
+                    ]),
+                    ..Default::default()
+                }),
+            },
+        )
+        .await
+        .inspect_err(metrics::record_http_error)?;
+
+    Ok(())
+}
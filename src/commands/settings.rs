@@ -0,0 +1,240 @@
+use std::fmt::Write;
+
+use twilight_model::{
+    guild::Permissions,
+    id::{marker::ChannelMarker, Id},
+};
+use vesper::prelude::*;
+
+use crate::{
+    hypervisor::languages::Languages,
+    parsers::{overlaps_default_prefix, DEFAULT_PREFIX},
+    settings::GuildSettings,
+    state::BotState,
+};
+
+use super::text_response;
+
+/// The longest prefix a guild may set.
+const MAX_PREFIX_LENGTH: usize = 10;
+
+#[derive(Parse, Clone, Copy)]
+pub enum AutoRun {
+    #[parse(rename = "Allowed")]
+    Allowed,
+    #[parse(rename = "Forbidden")]
+    Forbidden,
+    #[parse(rename = "Default")]
+    Default,
+}
+
+#[derive(Parse, Clone, Copy)]
+pub enum OutputMode {
+    #[parse(rename = "Pages")]
+    Paged,
+    #[parse(rename = "Separate stdout and stderr files")]
+    Split,
+}
+
+#[command(name = "settings")]
+#[description = "Shows or changes how the bot works in this server"]
+#[only_guilds]
+#[required_permissions(MANAGE_GUILD)]
+pub async fn settings(
+    ctx: &SlashContext<'_, BotState>,
+    #[description = "What starts a message whose code is run, `$>` by default"] prefix: Option<
+        String,
+    >,
+    #[description = "The languages that may be run, separated by commas, or `all`"]
+    languages: Option<String>,
+    #[description = "A channel to change whether code is run automatically in"] channel: Option<
+        Id<ChannelMarker>,
+    >,
+    #[description = "Whether code is run automatically in the channel"] auto_run: Option<AutoRun>,
+    #[description = "Whether editing a message runs its code again"] rerun_on_edit: Option<bool>,
+    #[description = "How long output is shown by default"] output: Option<OutputMode>,
+) -> DefaultCommandResult {
+    let Some(guild_id) = ctx.interaction.guild_id else {
+        return text_response(ctx, "Settings only apply to servers.".to_string(), true).await;
+    };
+    // the permissions of the command can be overridden by the server, this can't
+    let can_manage = ctx
+        .interaction
+        .member
+        .as_ref()
+        .and_then(|member| member.permissions)
+        .is_some_and(|permissions| permissions.contains(Permissions::MANAGE_GUILD));
+    if !can_manage {
+        return text_response(
+            ctx,
+            "Only members who can manage the server can change its settings.".to_string(),
+            true,
+        )
+        .await;
+    }
+
+    let current = ctx.data.settings.get(&ctx.data.db, Some(guild_id)).await?;
+    let mut settings = GuildSettings::clone(&current);
+
+    if let Some(prefix) = prefix {
+        let prefix = prefix.trim();
+        if prefix.is_empty()
+            || prefix.chars().count() > MAX_PREFIX_LENGTH
+            || prefix.contains(|c: char| c.is_whitespace() || c == '`')
+        {
+            return text_response(
+                ctx,
+                format!("The prefix must be 1 to {MAX_PREFIX_LENGTH} characters without spaces or backticks."),
+                true,
+            )
+            .await;
+        }
+        if prefix != DEFAULT_PREFIX && overlaps_default_prefix(prefix) {
+            return text_response(
+                ctx,
+                format!("The prefix can't overlap the default `{DEFAULT_PREFIX}`, as messages using it would be misread."),
+                true,
+            )
+            .await;
+        }
+        settings.prefix = prefix.to_string();
+    }
+
+    if let Some(languages) = languages {
+        settings.languages = match parse_languages(&languages) {
+            Ok(languages) => languages,
+            Err(language) => {
+                return text_response(ctx, format!("Unsupported language `{language}`"), true).await
+            }
+        };
+    }
+
+    match (channel, auto_run) {
+        (Some(channel), Some(auto_run)) => {
+            let channel = channel.to_string();
+            settings.allowed_channels.retain(|id| *id != channel);
+            settings.forbidden_channels.retain(|id| *id != channel);
+            match auto_run {
+                AutoRun::Allowed => settings.allowed_channels.push(channel),
+                AutoRun::Forbidden => settings.forbidden_channels.push(channel),
+                AutoRun::Default => {}
+            }
+        }
+        (None, None) => {}
+        _ => {
+            return text_response(
+                ctx,
+                "Give both a channel and whether code is run automatically in it.".to_string(),
+                true,
+            )
+            .await
+        }
+    }
+
+    if let Some(rerun_on_edit) = rerun_on_edit {
+        settings.rerun_on_edit = rerun_on_edit;
+    }
+    if let Some(output) = output {
+        settings.split_output = matches!(output, OutputMode::Split);
+    }
+
+    let content = if settings == *current {
+        format_settings(&settings)
+    } else {
+        let content = format!("Settings updated.\n{}", format_settings(&settings));
+        ctx.data
+            .settings
+            .set(&ctx.data.db, guild_id, settings)
+            .await?;
+
+        content
+    };
+
+    text_response(ctx, content, true).await
+}
+
+/// Parses a list of languages separated by commas, `all` meaning every enabled language.
+///
+/// Returns the language that isn't supported if there is one.
+fn parse_languages(input: &str) -> Result<Option<Vec<String>>, &str> {
+    if input.trim().eq_ignore_ascii_case("all") {
+        return Ok(None);
+    }
+
+    let mut languages = vec![];
+    for name in input
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        let Some(language) = Languages::from_codeblock_language(name).filter(Languages::enabled)
+        else {
+            return Err(name);
+        };
+        let language = language.to_string();
+        if !languages.contains(&language) {
+            languages.push(language);
+        }
+    }
+
+    if languages.is_empty() {
+        return Err(input);
+    }
+
+    Ok(Some(languages))
+}
+
+fn format_settings(settings: &GuildSettings) -> String {
+    let channels = |channels: &[String], empty: &str| {
+        if channels.is_empty() {
+            empty.to_string()
+        } else {
+            channels
+                .iter()
+                .map(|channel| format!("<#{channel}>"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    };
+
+    let mut out = "### Settings\n".to_string();
+    let _ = writeln!(out, "Prefix: `{}`", settings.prefix);
+    let _ = writeln!(
+        out,
+        "Languages: {}",
+        match &settings.languages {
+            Some(languages) => languages
+                .iter()
+                .map(|language| format!("`{language}`"))
+                .collect::<Vec<_>>()
+                .join(", "),
+            None => "every enabled language".to_string(),
+        }
+    );
+    let _ = writeln!(
+        out,
+        "Code is run automatically in: {}",
+        channels(&settings.allowed_channels, "every channel")
+    );
+    let _ = writeln!(
+        out,
+        "Code is never run automatically in: {}",
+        channels(&settings.forbidden_channels, "no channel")
+    );
+    let _ = writeln!(
+        out,
+        "Editing a message runs its code again: {}",
+        if settings.rerun_on_edit { "yes" } else { "no" }
+    );
+    let _ = write!(
+        out,
+        "Long output is shown as: {}",
+        if settings.split_output {
+            "separate stdout and stderr files"
+        } else {
+            "pages"
+        }
+    );
+
+    out
+}
@@ -0,0 +1,154 @@
+use std::fmt::Write;
+
+use sqlx::types::time::{Duration, OffsetDateTime};
+use twilight_model::id::{marker::GuildMarker, Id};
+use vesper::prelude::*;
+
+use crate::state::BotState;
+
+use super::{defer_response, edit_response, is_owner};
+
+/// How many languages are ranked.
+const TOP_LANGUAGES: i64 = 10;
+/// How many guilds are ranked.
+const TOP_GUILDS: i64 = 5;
+
+#[derive(Parse, Clone, Copy)]
+pub enum Period {
+    #[parse(rename = "Last 24 hours")]
+    Day,
+    #[parse(rename = "Last 7 days")]
+    Week,
+    #[parse(rename = "Last 30 days")]
+    Month,
+    #[parse(rename = "All time")]
+    All,
+}
+
+impl Period {
+    /// When the period starts.
+    fn since(self) -> OffsetDateTime {
+        let now = OffsetDateTime::now_utc();
+        match self {
+            Period::Day => now - Duration::days(1),
+            Period::Week => now - Duration::weeks(1),
+            Period::Month => now - Duration::days(30),
+            Period::All => OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Period::Day => "the last 24 hours",
+            Period::Week => "the last 7 days",
+            Period::Month => "the last 30 days",
+            Period::All => "all time",
+        }
+    }
+}
+
+#[command(name = "stats")]
+#[description = "Shows how the bot has been used"]
+pub async fn stats(
+    ctx: &SlashContext<'_, BotState>,
+    #[description = "The period to show, by default the last 7 days"] period: Option<Period>,
+) -> DefaultCommandResult {
+    let period = period.unwrap_or(Period::Week);
+    // which servers use the bot is only for owners to know
+    let owner = is_owner(ctx);
+    defer_response(ctx, owner).await?;
+
+    let since = period.since();
+    // statistics are of the last run of each execution
+    let totals = sqlx::query!(
+        r#"select
+            count(*) as "executions!",
+            coalesce(sum(runs), 0) as "runs!",
+            count(*) filter (where status = 'success') as "succeeded!",
+            count(*) filter (where status in ('failure', 'compile_failure')) as "failed!",
+            count(*) filter (where status = 'timeout') as "timed_out!",
+            count(*) filter (where status = 'error') as "errored!",
+            avg(duration_ms)::float8 as average_duration_ms
+        from execution where last_run_at >= $1"#,
+        since
+    )
+    .fetch_one(&ctx.data.db)
+    .await?;
+    if totals.executions == 0 {
+        return edit_response(ctx, format!("No code ran in {}.", period.label()), &[]).await;
+    }
+
+    let languages = sqlx::query!(
+        r#"select language, count(*) as "count!" from execution where last_run_at >= $1 group by language order by 2 desc, language limit $2"#,
+        since,
+        TOP_LANGUAGES
+    )
+    .fetch_all(&ctx.data.db)
+    .await?;
+    let guilds = if owner {
+        sqlx::query!(
+            r#"select guild_id as "guild_id!", count(*) as "count!" from execution where last_run_at >= $1 and guild_id is not null group by guild_id order by 2 desc limit $2"#,
+            since,
+            TOP_GUILDS
+        )
+        .fetch_all(&ctx.data.db)
+        .await?
+    } else {
+        vec![]
+    };
+
+    let percent = |count: i64| count as f64 / totals.executions as f64 * 100.0;
+    let mut content = format!("### Stats for {}\n", period.label());
+    let _ = writeln!(
+        content,
+        "**{}** executions, run **{}** times in total",
+        totals.executions, totals.runs
+    );
+    let _ = writeln!(
+        content,
+        "✅ {:.1}% succeeded • ❌ {:.1}% failed • ⏱️ {:.1}% timed out • ⚠️ {:.1}% errored",
+        percent(totals.succeeded),
+        percent(totals.failed),
+        percent(totals.timed_out),
+        percent(totals.errored)
+    );
+    if let Some(average) = totals.average_duration_ms {
+        let _ = writeln!(content, "Runs take **{:.2}s** on average", average / 1000.0);
+    }
+
+    content.push_str("\n**Languages**\n");
+    for (i, language) in languages.iter().enumerate() {
+        let _ = writeln!(
+            content,
+            "{}. `{}` • {} ({:.1}%)",
+            i + 1,
+            language.language,
+            language.count,
+            percent(language.count)
+        );
+    }
+
+    if !guilds.is_empty() {
+        content.push_str("\n**Busiest servers**\n");
+        for (i, guild) in guilds.iter().enumerate() {
+            let name = guild_name(ctx, &guild.guild_id).await;
+            let _ = writeln!(content, "{}. {name} • {}", i + 1, guild.count);
+        }
+    }
+
+    edit_response(ctx, content, &[]).await
+}
+
+/// The name of the guild with the id `guild_id`, or the id if the bot isn't in the guild.
+async fn guild_name(ctx: &SlashContext<'_, BotState>, guild_id: &str) -> String {
+    let Ok(id) = guild_id.parse::<Id<GuildMarker>>() else {
+        return format!("`{guild_id}`");
+    };
+    let guild = match ctx.http_client().guild(id).await {
+        Ok(res) => res.model().await.ok(),
+        // the bot isn't in guilds where only users installed it
+        Err(_) => None,
+    };
+
+    guild.map_or_else(|| format!("`{guild_id}`"), |guild| guild.name)
+}
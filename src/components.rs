@@ -0,0 +1,93 @@
+use twilight_model::{
+    application::interaction::{message_component::MessageComponentInteractionData, Interaction},
+    channel::message::{
+        component::{ActionRow, Component},
+        MessageFlags,
+    },
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+};
+
+use crate::{config::CONFIG, metrics, BotFramework};
+
+pub mod delete;
+pub mod history;
+pub mod history_run;
+pub mod page;
+pub mod rerun;
+pub mod show_code;
+pub mod stop;
+
+/// Routes a message component interaction to its handler.
+///
+/// Custom ids are formatted as `<component>:<arguments>`.
+pub async fn handle(
+    framework: BotFramework,
+    interaction: &Interaction,
+    data: &MessageComponentInteractionData,
+) -> anyhow::Result<()> {
+    let (component, args) = data
+        .custom_id
+        .split_once(':')
+        .unwrap_or((&data.custom_id, ""));
+
+    match component {
+        delete::ID => delete::handle(framework, interaction, args).await,
+        history::ID => history::handle(framework, interaction, args).await,
+        history_run::ID => history_run::handle(framework, interaction, args).await,
+        page::ID => page::handle(framework, interaction, args).await,
+        rerun::ID => rerun::handle(framework, interaction, args).await,
+        show_code::ID => show_code::handle(framework, interaction, args).await,
+        stop::ID => stop::handle(framework, interaction, args).await,
+        _ => {
+            tracing::warn!("received unknown component {}", data.custom_id);
+
+            Ok(())
+        }
+    }
+}
+
+/// A row with the buttons of a finished execution, identified by its id in the database.
+pub fn actions_row(execution: i64) -> Component {
+    Component::ActionRow(ActionRow {
+        components: vec![
+            rerun::button(execution),
+            show_code::button(execution),
+            delete::button(execution),
+        ],
+    })
+}
+
+pub async fn respond(
+    framework: &BotFramework,
+    interaction: &Interaction,
+    response: &InteractionResponse,
+) -> anyhow::Result<()> {
+    framework
+        .http_client()
+        .interaction(CONFIG.discord_application_id)
+        .create_response(interaction.id, &interaction.token, response)
+        .await
+        .inspect_err(metrics::record_http_error)?;
+
+    Ok(())
+}
+
+pub async fn ephemeral_response(
+    framework: &BotFramework,
+    interaction: &Interaction,
+    text: String,
+) -> anyhow::Result<()> {
+    respond(
+        framework,
+        interaction,
+        &InteractionResponse {
+            kind: InteractionResponseType::ChannelMessageWithSource,
+            data: Some(InteractionResponseData {
+                content: Some(text),
+                flags: Some(MessageFlags::EPHEMERAL),
+                ..Default::default()
+            }),
+        },
+    )
+    .await
+}
@@ -0,0 +1,98 @@
+use twilight_model::{
+    application::interaction::Interaction,
+    channel::message::component::{Button, ButtonStyle, Component},
+    guild::Permissions,
+    http::interaction::{InteractionResponse, InteractionResponseType},
+};
+
+use crate::{config::CONFIG, metrics, BotFramework};
+
+use super::{ephemeral_response, respond};
+
+pub const ID: &str = "delete";
+
+/// A button that deletes the reply of `execution`.
+///
+/// Only whoever ran the code and members who can manage messages are allowed to press it.
+pub fn button(execution: i64) -> Component {
+    Component::Button(Button {
+        custom_id: Some(format!("{ID}:{execution}")),
+        disabled: false,
+        emoji: None,
+        label: Some("Delete".to_string()),
+        style: ButtonStyle::Danger,
+        url: None,
+    })
+}
+
+pub async fn handle(
+    framework: BotFramework,
+    interaction: &Interaction,
+    args: &str,
+) -> anyhow::Result<()> {
+    let id = args.parse::<i64>()?;
+    let Some(message) = &interaction.message else {
+        anyhow::bail!("delete button pressed without a message");
+    };
+
+    let execution = sqlx::query!(
+        "select author_id, reply_id from execution where id = $1",
+        id
+    )
+    .fetch_optional(&framework.data.db)
+    .await?;
+    let is_author = execution
+        .as_ref()
+        .and_then(|execution| execution.author_id.as_ref())
+        .is_some_and(|author_id| {
+            interaction
+                .author_id()
+                .is_some_and(|user| &user.to_string() == author_id)
+        });
+    let is_moderator = interaction
+        .member
+        .as_ref()
+        .and_then(|member| member.permissions)
+        .is_some_and(|permissions| permissions.contains(Permissions::MANAGE_MESSAGES));
+    if !is_author && !is_moderator {
+        return ephemeral_response(
+            &framework,
+            interaction,
+            "Only the person who ran this code or a moderator can delete it.".to_string(),
+        )
+        .await;
+    }
+
+    respond(
+        &framework,
+        interaction,
+        &InteractionResponse {
+            kind: InteractionResponseType::DeferredUpdateMessage,
+            data: None,
+        },
+    )
+    .await?;
+
+    // the bot may not see the channel of a reply to an interaction, like in DMs or where only
+    // the user installed it, but the reply can be deleted through the button's interaction
+    if execution.is_some_and(|execution| execution.reply_id == "interaction") {
+        framework
+            .http_client()
+            .interaction(CONFIG.discord_application_id)
+            .delete_response(&interaction.token)
+            .await
+            .inspect_err(metrics::record_http_error)?;
+    } else {
+        framework
+            .http_client()
+            .delete_message(message.channel_id, message.id)
+            .await
+            .inspect_err(metrics::record_http_error)?;
+    }
+
+    sqlx::query!("update execution set deleted_at = now() where id = $1", id)
+        .execute(&framework.data.db)
+        .await?;
+
+    Ok(())
+}
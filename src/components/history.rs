@@ -0,0 +1,158 @@
+use std::fmt::Write;
+
+use sqlx::PgPool;
+use twilight_model::{
+    application::interaction::Interaction,
+    channel::message::{
+        component::{ActionRow, Button, ButtonStyle, Component},
+        MessageFlags,
+    },
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    id::{marker::UserMarker, Id},
+};
+
+use crate::{reply::Status, BotFramework};
+
+use super::{history_run, respond};
+
+pub const ID: &str = "history";
+
+/// How many executions are listed per page, at most as many as buttons fit in a row.
+const PAGE_SIZE: i64 = 5;
+
+/// Lists `page` of the executions `user` ran, most recently run first, with buttons to run
+/// each of them again and to flip through the pages.
+pub async fn render(
+    db: &PgPool,
+    user: Id<UserMarker>,
+    page: i64,
+) -> anyhow::Result<InteractionResponseData> {
+    let author_id = user.to_string();
+    let total = sqlx::query!(
+        "select count(*) as count from execution where author_id = $1 and deleted_at is null",
+        author_id
+    )
+    .fetch_one(db)
+    .await?
+    .count
+    .unwrap_or(0);
+    if total == 0 {
+        return Ok(InteractionResponseData {
+            content: Some("You haven't run any code yet.".to_string()),
+            components: Some(vec![]),
+            flags: Some(MessageFlags::EPHEMERAL),
+            ..Default::default()
+        });
+    }
+
+    let pages = (total + PAGE_SIZE - 1) / PAGE_SIZE;
+    let page = page.clamp(0, pages - 1);
+    let executions = sqlx::query!(
+        "select id, guild_id, channel_id, reply_id, language, status, runs, last_run_at from execution where author_id = $1 and deleted_at is null order by last_run_at desc limit $2 offset $3",
+        author_id,
+        PAGE_SIZE,
+        page * PAGE_SIZE
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut content = "### Your executions\n".to_string();
+    let mut buttons = vec![];
+    for (i, execution) in executions.iter().enumerate() {
+        let n = page * PAGE_SIZE + i as i64 + 1;
+        let status = execution
+            .status
+            .as_deref()
+            .and_then(Status::parse)
+            .map_or("❔ No result", |status| status.label());
+        let _ = write!(
+            content,
+            "{n}. `{}` • <t:{}:R> • {status}",
+            execution.language,
+            execution.last_run_at.unix_timestamp()
+        );
+        if execution.runs > 1 {
+            let _ = write!(content, " • ran {} times", execution.runs);
+        }
+        // replies to interactions can't be linked to
+        match &execution.channel_id {
+            Some(channel_id) if execution.reply_id != "interaction" => {
+                let guild = execution.guild_id.as_deref().unwrap_or("@me");
+                let _ = write!(
+                    content,
+                    " • [Jump](https://discord.com/channels/{guild}/{channel_id}/{})",
+                    execution.reply_id
+                );
+            }
+            _ => {}
+        }
+        content.push('\n');
+
+        buttons.push(history_run::button(execution.id, format!("Run {n} again")));
+    }
+
+    Ok(InteractionResponseData {
+        content: Some(content),
+        components: Some(vec![
+            Component::ActionRow(ActionRow {
+                components: buttons,
+            }),
+            row(page, pages),
+        ]),
+        flags: Some(MessageFlags::EPHEMERAL),
+        ..Default::default()
+    })
+}
+
+/// A row with buttons flipping through the pages of the history, on `page` of `pages`.
+fn row(page: i64, pages: i64) -> Component {
+    let button = |id: String, label: String, disabled: bool| {
+        Component::Button(Button {
+            custom_id: Some(format!("{ID}:{id}")),
+            disabled,
+            emoji: None,
+            label: Some(label),
+            style: ButtonStyle::Secondary,
+            url: None,
+        })
+    };
+
+    Component::ActionRow(ActionRow {
+        components: vec![
+            button(
+                (page - 1).max(0).to_string(),
+                "Previous".to_string(),
+                page == 0,
+            ),
+            // only a label, but buttons need a unique id
+            button("current".to_string(), format!("{}/{pages}", page + 1), true),
+            button(
+                (page + 1).to_string(),
+                "Next".to_string(),
+                page + 1 >= pages,
+            ),
+        ],
+    })
+}
+
+pub async fn handle(
+    framework: BotFramework,
+    interaction: &Interaction,
+    args: &str,
+) -> anyhow::Result<()> {
+    let page = args.parse::<i64>()?;
+    let Some(user) = interaction.author_id() else {
+        anyhow::bail!("history button pressed without a user");
+    };
+
+    let data = render(&framework.data.db, user, page).await?;
+    respond(
+        &framework,
+        interaction,
+        &InteractionResponse {
+            kind: InteractionResponseType::UpdateMessage,
+            data: Some(data),
+        },
+    )
+    .await
+}
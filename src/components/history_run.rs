@@ -0,0 +1,30 @@
+use twilight_model::{
+    application::interaction::Interaction,
+    channel::message::component::{Button, ButtonStyle, Component},
+};
+
+use crate::BotFramework;
+
+use super::rerun::{self, Target};
+
+pub const ID: &str = "history_run";
+
+/// A button that runs the code of `execution` again in a new message, labeled `label`.
+pub fn button(execution: i64, label: String) -> Component {
+    Component::Button(Button {
+        custom_id: Some(format!("{ID}:{execution}")),
+        disabled: false,
+        emoji: None,
+        label: Some(label),
+        style: ButtonStyle::Primary,
+        url: None,
+    })
+}
+
+pub async fn handle(
+    framework: BotFramework,
+    interaction: &Interaction,
+    args: &str,
+) -> anyhow::Result<()> {
+    rerun::run(framework, interaction, args.parse()?, Target::FollowUp).await
+}
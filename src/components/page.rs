@@ -0,0 +1,128 @@
+use std::str::FromStr;
+
+use twilight_model::{
+    application::interaction::Interaction,
+    channel::message::component::{ActionRow, Button, ButtonStyle, Component},
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    id::{marker::GenericMarker, Id},
+};
+
+use crate::{render::Paged, state::PagedOutputs, BotFramework};
+
+use super::{ephemeral_response, respond};
+
+pub const ID: &str = "page";
+
+/// A row with buttons flipping through the pages of the output stored under `key`, on `page`
+/// of `pages`.
+pub fn row(key: Id<GenericMarker>, page: usize, pages: usize) -> Component {
+    let button = |id: String, label: String, disabled: bool| {
+        Component::Button(Button {
+            custom_id: Some(format!("{ID}:{key}:{id}")),
+            disabled,
+            emoji: None,
+            label: Some(label),
+            style: ButtonStyle::Secondary,
+            url: None,
+        })
+    };
+
+    Component::ActionRow(ActionRow {
+        components: vec![
+            button(
+                page.saturating_sub(1).to_string(),
+                "Previous".to_string(),
+                page == 0,
+            ),
+            // only a label, but buttons need a unique id
+            button("current".to_string(), format!("{}/{pages}", page + 1), true),
+            button(
+                (page + 1).to_string(),
+                "Next".to_string(),
+                page + 1 >= pages,
+            ),
+        ],
+    })
+}
+
+/// Stores the paginated output of the execution under `key`, returning the components its
+/// reply needs.
+pub fn store(
+    outputs: &PagedOutputs,
+    key: Id<GenericMarker>,
+    paged: Option<Paged>,
+) -> Vec<Component> {
+    match paged {
+        Some(paged) => {
+            let row = row(key, 0, paged.pages.len());
+            outputs.insert(key, paged);
+
+            vec![row]
+        }
+        None => {
+            outputs.remove(key);
+
+            vec![]
+        }
+    }
+}
+
+pub async fn handle(
+    framework: BotFramework,
+    interaction: &Interaction,
+    args: &str,
+) -> anyhow::Result<()> {
+    let Some((key, page)) = args.split_once(':') else {
+        anyhow::bail!("malformed page button arguments `{args}`");
+    };
+    let key = Id::<GenericMarker>::from_str(key)?;
+    let page = page.parse::<usize>()?;
+
+    let Some(paged) = framework.data.pages.get(key) else {
+        return ephemeral_response(
+            &framework,
+            interaction,
+            "This output has expired, run the code again to page through it.".to_string(),
+        )
+        .await;
+    };
+    let page = page.min(paged.pages.len().saturating_sub(1));
+
+    // keep the other rows of the reply as they are
+    let components = interaction
+        .message
+        .iter()
+        .flat_map(|message| &message.components)
+        .map(|component| {
+            if is_page_row(component) {
+                row(key, page, paged.pages.len())
+            } else {
+                component.clone()
+            }
+        })
+        .collect();
+
+    respond(
+        &framework,
+        interaction,
+        &InteractionResponse {
+            kind: InteractionResponseType::UpdateMessage,
+            data: Some(InteractionResponseData {
+                content: Some(paged.page(page)),
+                components: Some(components),
+                ..Default::default()
+            }),
+        },
+    )
+    .await
+}
+
+fn is_page_row(component: &Component) -> bool {
+    let Component::ActionRow(row) = component else {
+        return false;
+    };
+
+    row.components.iter().any(|component| {
+        matches!(component, Component::Button(Button { custom_id: Some(id), .. }) if id.split_once(':').is_some_and(|(component, _)| component == ID))
+    })
+}
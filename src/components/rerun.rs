@@ -0,0 +1,195 @@
+use std::str::FromStr;
+use std::time::Instant;
+
+use twilight_model::{
+    application::interaction::Interaction,
+    channel::message::{
+        component::{ActionRow, Button, ButtonStyle, Component},
+        MessageFlags,
+    },
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    id::{marker::GenericMarker, Id},
+};
+
+use crate::{
+    config::CONFIG,
+    execution::{self, Finish},
+    reply::{execute, Reply},
+    BotFramework,
+};
+
+use super::{actions_row, ephemeral_response, history_run, respond, show_code, stop};
+
+pub const ID: &str = "rerun";
+
+/// A button that runs the code of `execution` again, updating its reply.
+pub fn button(execution: i64) -> Component {
+    Component::Button(Button {
+        custom_id: Some(format!("{ID}:{execution}")),
+        disabled: false,
+        emoji: None,
+        label: Some("Run again".to_string()),
+        style: ButtonStyle::Primary,
+        url: None,
+    })
+}
+
+/// Where the output of an execution that's run again goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// The message with the button, which is the reply of the execution
+    Reply,
+    /// A new message only the user who pressed the button sees
+    FollowUp,
+}
+
+pub async fn handle(
+    framework: BotFramework,
+    interaction: &Interaction,
+    args: &str,
+) -> anyhow::Result<()> {
+    run(framework, interaction, args.parse()?, Target::Reply).await
+}
+
+/// Runs the code of the execution with the database id `id` again, showing its output in
+/// `target`.
+pub async fn run(
+    framework: BotFramework,
+    interaction: &Interaction,
+    id: i64,
+    target: Target,
+) -> anyhow::Result<()> {
+    let Some(execution) = sqlx::query!(
+        "select message_id, reply_id, language, code, source, stdin from execution where id = $1",
+        id
+    )
+    .fetch_optional(&framework.data.db)
+    .await?
+    else {
+        return ephemeral_response(
+            &framework,
+            interaction,
+            "This execution is not available anymore.".to_string(),
+        )
+        .await;
+    };
+    let Some(code) = execution::stored_code(
+        &execution.language,
+        execution.code.as_deref(),
+        execution.source.as_deref(),
+        execution.stdin.as_deref(),
+    ) else {
+        return ephemeral_response(
+            &framework,
+            interaction,
+            "The code of this execution is not available anymore.".to_string(),
+        )
+        .await;
+    };
+    let settings = framework
+        .data
+        .settings
+        .get(&framework.data.db, interaction.guild_id)
+        .await?;
+    let (language, options) = match execution::prepare(
+        &framework.data,
+        &code,
+        &settings,
+        interaction.author_id(),
+        interaction.guild_id,
+    )
+    .await?
+    {
+        Ok(prepared) => prepared,
+        Err(rejection) => {
+            return ephemeral_response(&framework, interaction, rejection.to_string()).await
+        }
+    };
+
+    sqlx::query!(
+        "update execution set runs = runs + 1, last_run_at = now() where id = $1",
+        id
+    )
+    .execute(&framework.data.db)
+    .await?;
+
+    // supersedes whatever else is running in the same reply
+    let key = match target {
+        Target::Reply => Id::<GenericMarker>::from_str(&execution.message_id)?,
+        Target::FollowUp => interaction.id.cast(),
+    };
+    let running = framework.data.running.start(key);
+    let components = interaction
+        .author_id()
+        .map(|user| vec![stop::row(running.key(), user)])
+        .unwrap_or_default();
+    let (kind, flags) = match target {
+        Target::Reply => (InteractionResponseType::UpdateMessage, None),
+        Target::FollowUp => (
+            InteractionResponseType::ChannelMessageWithSource,
+            Some(MessageFlags::EPHEMERAL),
+        ),
+    };
+    respond(
+        &framework,
+        interaction,
+        &InteractionResponse {
+            kind,
+            data: Some(InteractionResponseData {
+                content: Some("-# ⏳ Running your code...".to_string()),
+                components: Some(components.clone()),
+                flags,
+                ..Default::default()
+            }),
+        },
+    )
+    .await?;
+
+    // after an update, the original response of a component interaction is its message, and
+    // otherwise it's the new message
+    let client = framework
+        .http_client()
+        .interaction(CONFIG.discord_application_id);
+    let reply = Reply::Interaction {
+        client: &client,
+        token: &interaction.token,
+    };
+    let started = Instant::now();
+    let result = execute(
+        &reply,
+        &framework.data.hypervisor,
+        &language,
+        &code,
+        options,
+        running.token(),
+        &components,
+    )
+    .await;
+    let actions = match target {
+        Target::Reply => actions_row(id),
+        // the reply of the execution is elsewhere, and this message can only be dismissed
+        Target::FollowUp => Component::ActionRow(ActionRow {
+            components: vec![
+                history_run::button(id, "Run again".to_string()),
+                show_code::button(id),
+            ],
+        }),
+    };
+    execution::finish(
+        &framework.data,
+        &reply,
+        &running,
+        result,
+        started.elapsed(),
+        Finish {
+            id,
+            actions,
+            edit_hint: target == Target::Reply
+                && execution.reply_id != "interaction"
+                && settings.rerun_on_edit,
+        },
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use std::fmt::Write;
+
+use twilight_model::{
+    application::interaction::Interaction,
+    channel::message::{
+        component::{Button, ButtonStyle, Component},
+        MessageFlags,
+    },
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+};
+
+use crate::{execution, parsers::MatchedCode, render, BotFramework};
+
+use super::{ephemeral_response, respond};
+
+pub const ID: &str = "show_code";
+
+/// The longest code shown in the response itself.
+const MAX_INLINE_CODE: usize = 1900;
+
+/// A button that shows the code `execution` ran to whoever presses it.
+pub fn button(execution: i64) -> Component {
+    Component::Button(Button {
+        custom_id: Some(format!("{ID}:{execution}")),
+        disabled: false,
+        emoji: None,
+        label: Some("Show code".to_string()),
+        style: ButtonStyle::Secondary,
+        url: None,
+    })
+}
+
+pub async fn handle(
+    framework: BotFramework,
+    interaction: &Interaction,
+    args: &str,
+) -> anyhow::Result<()> {
+    let id = args.parse::<i64>()?;
+    let execution = sqlx::query!(
+        "select language, code, source, stdin from execution where id = $1",
+        id
+    )
+    .fetch_optional(&framework.data.db)
+    .await?;
+    let Some(code) = execution.as_ref().and_then(|execution| {
+        execution::stored_code(
+            &execution.language,
+            execution.code.as_deref(),
+            execution.source.as_deref(),
+            execution.stdin.as_deref(),
+        )
+    }) else {
+        return ephemeral_response(
+            &framework,
+            interaction,
+            "The code of this execution is not available anymore.".to_string(),
+        )
+        .await;
+    };
+
+    let formatted = format_code(&code);
+    let data = if formatted.chars().count() <= MAX_INLINE_CODE {
+        InteractionResponseData {
+            content: Some(formatted),
+            flags: Some(MessageFlags::EPHEMERAL),
+            ..Default::default()
+        }
+    } else {
+        InteractionResponseData {
+            content: Some("-# 📎 The code is too long to show, see the attached file".to_string()),
+            attachments: Some(vec![render::attachment(
+                "code.md".to_string(),
+                &formatted,
+                0,
+            )]),
+            flags: Some(MessageFlags::EPHEMERAL),
+            ..Default::default()
+        }
+    };
+
+    respond(
+        &framework,
+        interaction,
+        &InteractionResponse {
+            kind: InteractionResponseType::ChannelMessageWithSource,
+            data: Some(data),
+        },
+    )
+    .await
+}
+
+/// Formats code as it was given: its code block with options, then its input.
+fn format_code(code: &MatchedCode<'_>) -> String {
+    let mut out = format!("```{}{}\n{}\n```", code.language, code.options, code.code);
+    if code.cases.is_empty() {
+        if let Some(stdin) = code.stdin {
+            let _ = write!(out, "\n```in\n{stdin}\n```");
+        }
+    } else {
+        for case in &code.cases {
+            let _ = write!(
+                out,
+                "\n```in\n{}\n```\n```out\n{}\n```",
+                case.input, case.expected
+            );
+        }
+    }
+
+    out
+}
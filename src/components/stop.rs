@@ -0,0 +1,68 @@
+use std::str::FromStr;
+
+use twilight_model::{
+    application::interaction::Interaction,
+    channel::message::component::{ActionRow, Button, ButtonStyle, Component},
+    http::interaction::{InteractionResponse, InteractionResponseType},
+    id::{
+        marker::{GenericMarker, UserMarker},
+        Id,
+    },
+};
+
+use crate::BotFramework;
+
+use super::{ephemeral_response, respond};
+
+pub const ID: &str = "stop";
+
+/// A row with a button that stops the execution running under `key`.
+///
+/// Only `user` (whoever started the execution) is allowed to press it.
+pub fn row(key: Id<GenericMarker>, user: Id<UserMarker>) -> Component {
+    Component::ActionRow(ActionRow {
+        components: vec![Component::Button(Button {
+            custom_id: Some(format!("{ID}:{key}:{user}")),
+            disabled: false,
+            emoji: None,
+            label: Some("Stop".to_string()),
+            style: ButtonStyle::Danger,
+            url: None,
+        })],
+    })
+}
+
+pub async fn handle(
+    framework: BotFramework,
+    interaction: &Interaction,
+    args: &str,
+) -> anyhow::Result<()> {
+    let Some((key, user)) = args.split_once(':') else {
+        anyhow::bail!("malformed stop button arguments `{args}`");
+    };
+    let key = Id::<GenericMarker>::from_str(key)?;
+    let user = Id::<UserMarker>::from_str(user)?;
+
+    if interaction.author_id() != Some(user) {
+        return ephemeral_response(
+            &framework,
+            interaction,
+            "Only the person who ran this code can stop it.".to_string(),
+        )
+        .await;
+    }
+
+    // the execution shows that it stopped itself, as only it knows the buttons of its reply, and
+    // if it finished in the meantime its result is on the way
+    framework.data.running.stop(key);
+
+    respond(
+        &framework,
+        interaction,
+        &InteractionResponse {
+            kind: InteractionResponseType::DeferredUpdateMessage,
+            data: None,
+        },
+    )
+    .await
+}
@@ -1,7 +1,10 @@
 use anyhow::Result;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use twilight_model::id::{marker::ApplicationMarker, Id};
+use twilight_model::id::{
+    marker::{ApplicationMarker, GuildMarker, UserMarker},
+    Id,
+};
 
 use crate::hypervisor::languages::Languages;
 
@@ -39,6 +42,17 @@ pub struct Config {
     /// The Discord application id for the bot
     pub discord_application_id: Id<ApplicationMarker>,
 
+    /// The guild to register commands in instead of globally, for development
+    pub dev_guild_id: Option<Id<GuildMarker>>,
+
+    /// The users who can manage the blocklist
+    #[serde(default)]
+    pub owner_ids: Vec<Id<UserMarker>>,
+
+    /// Whether blocked users are told so instead of being ignored
+    #[serde(default)]
+    pub block_notice: bool,
+
     /// The enabled languages
     pub languages: Vec<Languages>,
 
@@ -50,6 +64,98 @@ pub struct Config {
 
     /// The docker runtime
     pub docker_runtime: String,
+
+    /// How many containers may run at once, further executions wait for one of them to finish
+    #[serde(default = "default_max_concurrent_executions")]
+    pub max_concurrent_executions: usize,
+
+    /// The most measured runs a benchmark may do
+    #[serde(default = "default_bench_max_runs")]
+    pub bench_max_runs: usize,
+
+    /// How long a benchmark may take in total, in seconds
+    #[serde(default = "default_bench_time_budget_secs")]
+    pub bench_time_budget_secs: u64,
+
+    /// The most test cases a submission may be judged against
+    #[serde(default = "default_judge_max_cases")]
+    pub judge_max_cases: usize,
+
+    /// The largest file output may be attached as, in bytes
+    #[serde(default = "default_max_attachment_size")]
+    pub max_attachment_size: usize,
+
+    /// How long paginated output can be paged through, in seconds
+    #[serde(default = "default_page_retention_secs")]
+    pub page_retention_secs: u64,
+
+    /// How long to wait for further edits of a message before running it again, in milliseconds
+    #[serde(default = "default_edit_debounce_ms")]
+    pub edit_debounce_ms: u64,
+
+    /// How many executions a user can run in quick succession
+    #[serde(default = "default_rate_limit_user_burst")]
+    pub rate_limit_user_burst: u32,
+
+    /// How long it takes for a user to be allowed one more execution, in milliseconds
+    #[serde(default = "default_rate_limit_user_refill_ms")]
+    pub rate_limit_user_refill_ms: u64,
+
+    /// How many executions can run in quick succession in a guild
+    #[serde(default = "default_rate_limit_guild_burst")]
+    pub rate_limit_guild_burst: u32,
+
+    /// How long it takes for a guild to be allowed one more execution, in milliseconds
+    #[serde(default = "default_rate_limit_guild_refill_ms")]
+    pub rate_limit_guild_refill_ms: u64,
+
+    /// The address to serve Prometheus metrics on, if any
+    #[cfg(feature = "metrics")]
+    pub metrics_address: Option<std::net::SocketAddr>,
+}
+
+fn default_max_concurrent_executions() -> usize {
+    8
+}
+
+fn default_bench_max_runs() -> usize {
+    20
+}
+
+fn default_bench_time_budget_secs() -> u64 {
+    30
+}
+
+fn default_judge_max_cases() -> usize {
+    10
+}
+
+fn default_max_attachment_size() -> usize {
+    8 * 1024 * 1024
+}
+
+fn default_page_retention_secs() -> u64 {
+    60 * 60
+}
+
+fn default_edit_debounce_ms() -> u64 {
+    1500
+}
+
+fn default_rate_limit_user_burst() -> u32 {
+    5
+}
+
+fn default_rate_limit_user_refill_ms() -> u64 {
+    12_000
+}
+
+fn default_rate_limit_guild_burst() -> u32 {
+    30
+}
+
+fn default_rate_limit_guild_refill_ms() -> u64 {
+    2000
 }
 
 impl Config {
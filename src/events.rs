@@ -1,4 +1,6 @@
 pub mod interaction_create;
 pub mod message_create;
+pub mod message_delete;
+pub mod message_delete_bulk;
 pub mod message_update;
 pub mod thread_create;
@@ -3,11 +3,28 @@ use twilight_model::{
     gateway::payload::incoming::InteractionCreate,
 };
 
-use crate::BotFramework;
+use crate::{autocomplete, components, config::CONFIG, modals, BotFramework};
 
 pub async fn handle(framework: BotFramework, event: Box<InteractionCreate>) -> anyhow::Result<()> {
     let interaction = event.0;
 
+    let channel = interaction.channel.as_ref().map(|channel| channel.id);
+    if let Some(entry) =
+        framework
+            .data
+            .blocklist
+            .check(interaction.author_id(), interaction.guild_id, channel)
+    {
+        // autocomplete can't be answered with a message
+        if CONFIG.block_notice
+            && interaction.kind != InteractionType::ApplicationCommandAutocomplete
+        {
+            return components::ephemeral_response(&framework, &interaction, entry.notice()).await;
+        }
+
+        return Ok(());
+    }
+
     match interaction.kind {
         InteractionType::ApplicationCommand => match interaction.data {
             Some(InteractionData::ApplicationCommand(_)) => {
@@ -17,6 +34,24 @@ pub async fn handle(framework: BotFramework, event: Box<InteractionCreate>) -> a
             }
             _ => unreachable!(),
         },
+        InteractionType::ApplicationCommandAutocomplete => match &interaction.data {
+            Some(InteractionData::ApplicationCommand(data)) => {
+                autocomplete::handle(framework, &interaction, data).await
+            }
+            _ => unreachable!(),
+        },
+        InteractionType::MessageComponent => match &interaction.data {
+            Some(InteractionData::MessageComponent(data)) => {
+                components::handle(framework, &interaction, data).await
+            }
+            _ => unreachable!(),
+        },
+        InteractionType::ModalSubmit => match &interaction.data {
+            Some(InteractionData::ModalSubmit(data)) => {
+                modals::handle(framework, &interaction, data).await
+            }
+            _ => unreachable!(),
+        },
         _ => unreachable!(),
     }
 }
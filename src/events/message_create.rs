@@ -1,7 +1,13 @@
+use std::time::Instant;
+
 use twilight_model::gateway::payload::incoming::MessageCreate;
 
 use crate::{
-    hypervisor::{format_output, languages::Languages},
+    components::{actions_row, stop},
+    config::CONFIG,
+    execution::{self, Finish},
+    metrics, parsers,
+    reply::{execute, Reply},
     BotFramework,
 };
 
@@ -11,100 +17,133 @@ pub async fn handle(framework: BotFramework, event: Box<MessageCreate>) -> anyho
         return Ok(());
     }
 
-    // todo: blocklist
-
-    let Some(code) = crate::parsers::match_code(&message.content, false) else {
+    let settings = framework
+        .data
+        .settings
+        .get(&framework.data.db, message.guild_id)
+        .await?;
+    if !settings.auto_runs_in(message.channel_id) {
+        return Ok(());
+    }
+    let Some(content) = parsers::replace_prefix(&message.content, &settings.prefix) else {
         return Ok(());
     };
-    tracing::info!("matched code: {code:#?}");
-    let Some(language) = Languages::from_codeblock_language(code.language) else {
-        if let Err(e) = framework
-            .http_client()
-            .create_message(message.channel_id)
-            .reply(message.id)
-            .content(&format!(
-                "Unsupported language `{language}`",
-                language = code.language
-            ))
-            .await
-        {
-            tracing::error!("failed to reply to message - {e}");
-        }
-
+    let Some(code) = parsers::match_code(&content, false) else {
         return Ok(());
     };
-    if !language.enabled() {
-        if let Err(e) = framework
-            .http_client()
-            .create_message(message.channel_id)
-            .reply(message.id)
-            .content(&format!(
-                "Unsupported language `{language}`",
-                language = code.language
-            ))
-            .await
-        {
-            tracing::error!("failed to reply to message - {e}");
+    if let Some(entry) = framework.data.blocklist.check(
+        Some(message.author.id),
+        message.guild_id,
+        Some(message.channel_id),
+    ) {
+        if CONFIG.block_notice {
+            if let Err(e) = framework
+                .http_client()
+                .create_message(message.channel_id)
+                .reply(message.id)
+                .content(&entry.notice())
+                .await
+            {
+                tracing::error!("failed to reply to message - {e}");
+                metrics::record_http_error(&e);
+            }
         }
 
         return Ok(());
     }
-
-    let _ = framework
-        .http_client()
-        .create_typing_trigger(message.channel_id)
-        .await?;
-
-    let code_result = match framework.data.hypervisor.exec(&language, code.code).await {
-        Ok(res) => res,
-        Err(e) => {
-            tracing::error!("failed to execute code - {e:#?}");
-
+    tracing::info!("matched code: {code:#?}");
+    let (language, options) = match execution::prepare(
+        &framework.data,
+        &code,
+        &settings,
+        Some(message.author.id),
+        message.guild_id,
+    )
+    .await?
+    {
+        Ok(prepared) => prepared,
+        Err(rejection) => {
             if let Err(e) = framework
                 .http_client()
                 .create_message(message.channel_id)
                 .reply(message.id)
-                .content(&format!("Failed to execute code: {e}"))
+                .content(&rejection.to_string())
                 .await
             {
                 tracing::error!("failed to reply to message - {e}");
+                metrics::record_http_error(&e);
             }
 
             return Ok(());
         }
     };
 
-    let out = format_output(code_result);
-
+    let running = framework.data.running.start(message.id.cast());
+    let components = [stop::row(running.key(), message.author.id)];
     let res = match framework
         .http_client()
         .create_message(message.channel_id)
         .reply(message.id)
-        .content(&format!(
-            "```{language}\n{out}\n```\n-# ℹ️ Edit your message and the output will update"
-        ))
+        .content("-# ⏳ Running your code...")
+        .components(&components)
         .await
     {
         Ok(res) => res.model().await?,
         Err(e) => {
             tracing::error!("failed to reply to message - {e}");
+            metrics::record_http_error(&e);
             return Ok(());
         }
     };
 
     let channel_id = message.channel_id.to_string();
     let message_id = message.id.to_string();
-    let language = language.to_string();
+    let language_name = language.to_string();
     let reply_id = res.id.to_string();
-    sqlx::query!(
-        "insert into execution (channel_id, message_id, language, reply_id) values ($1, $2, $3, $4) returning *;",
+    let author_id = message.author.id.to_string();
+    let guild_id = message.guild_id.map(|guild| guild.to_string());
+    let execution = sqlx::query!(
+        "insert into execution (channel_id, message_id, language, reply_id, author_id, code, guild_id) values ($1, $2, $3, $4, $5, $6, $7) returning *;",
         channel_id,
         message_id,
-        language,
-        reply_id
+        language_name,
+        reply_id,
+        author_id,
+        message.content,
+        guild_id
     )
     .fetch_one(&framework.data.db)
     .await?;
 
+    let reply = Reply::Message {
+        http: framework.http_client(),
+        channel_id: message.channel_id,
+        message_id: res.id,
+    };
+    let started = Instant::now();
+    let result = execute(
+        &reply,
+        &framework.data.hypervisor,
+        &language,
+        &code,
+        options,
+        running.token(),
+        &components,
+    )
+    .await;
+    execution::finish(
+        &framework.data,
+        &reply,
+        &running,
+        result,
+        started.elapsed(),
+        Finish {
+            id: execution.id,
+            actions: actions_row(execution.id),
+            edit_hint: settings.rerun_on_edit,
+        },
+    )
+    .await?;
+
     Ok(())
 }
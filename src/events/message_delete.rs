@@ -0,0 +1,58 @@
+use std::str::FromStr;
+
+use twilight_model::{
+    gateway::payload::incoming::MessageDelete,
+    id::{marker::MessageMarker, Id},
+};
+
+use crate::{metrics, BotFramework};
+
+pub async fn handle(framework: BotFramework, event: MessageDelete) -> anyhow::Result<()> {
+    delete_replies(&framework, &[event.id]).await
+}
+
+/// Deletes the replies to the deleted `$>` messages `message_ids` and marks their executions as
+/// deleted, stopping them if they're still running.
+///
+/// Replies to the "Execute Code" command are left alone, they don't belong to the message.
+pub(crate) async fn delete_replies(
+    framework: &BotFramework,
+    message_ids: &[Id<MessageMarker>],
+) -> anyhow::Result<()> {
+    let ids = message_ids
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    let executions = sqlx::query!(
+        "update execution set deleted_at = now() where message_id = any($1) and reply_id <> 'interaction' and deleted_at is null returning channel_id, reply_id",
+        &ids
+    )
+    .fetch_all(&framework.data.db)
+    .await?;
+
+    for id in message_ids {
+        framework.data.running.cancel(id.cast());
+        framework.data.pages.remove(id.cast());
+    }
+
+    for execution in executions {
+        let (Some(Ok(channel_id)), Ok(reply_id)) = (
+            execution.channel_id.as_deref().map(Id::from_str),
+            Id::from_str(&execution.reply_id),
+        ) else {
+            tracing::warn!("execution has a malformed reply {}", execution.reply_id);
+            continue;
+        };
+
+        if let Err(e) = framework
+            .http_client()
+            .delete_message(channel_id, reply_id)
+            .await
+        {
+            tracing::error!("failed to delete reply - {e}");
+            metrics::record_http_error(&e);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,9 @@
+use twilight_model::gateway::payload::incoming::MessageDeleteBulk;
+
+use crate::BotFramework;
+
+use super::message_delete::delete_replies;
+
+pub async fn handle(framework: BotFramework, event: MessageDeleteBulk) -> anyhow::Result<()> {
+    delete_replies(&framework, &event.ids).await
+}
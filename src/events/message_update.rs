@@ -1,77 +1,196 @@
 use std::str::FromStr;
+use std::time::{Duration, Instant};
 
+use sqlx::types::time::OffsetDateTime;
 use twilight_model::{
     gateway::payload::incoming::MessageUpdate,
     id::{marker::MessageMarker, Id},
 };
 
 use crate::{
-    hypervisor::{format_output, languages::Languages},
+    components::{actions_row, stop},
+    config::CONFIG,
+    execution::{self, Finish},
+    parsers,
+    reply::{execute, Reply},
     BotFramework,
 };
 
+use super::message_delete::delete_replies;
+
 pub async fn handle(framework: BotFramework, message: Box<MessageUpdate>) -> anyhow::Result<()> {
+    // updates without content, like embeds being resolved, didn't change the code
+    let Some(content) = message.content.as_deref() else {
+        return Ok(());
+    };
     let message_id = message.id.to_string();
-    let Some(existing_execution) =
-        sqlx::query!("select * from execution where message_id = $1", message_id)
-            .fetch_optional(&framework.data.db)
-            .await?
+    // replies to the context menu command aren't updated on edits
+    let Some(existing_execution) = sqlx::query!(
+        "select * from execution where message_id = $1 and reply_id <> 'interaction' and deleted_at is null order by id desc limit 1",
+        message_id
+    )
+    .fetch_optional(&framework.data.db)
+    .await?
     else {
         return Ok(());
     };
-    let content = message.content.unwrap();
+    let settings = framework
+        .data
+        .settings
+        .get(&framework.data.db, message.guild_id)
+        .await?;
+    if !settings.rerun_on_edit {
+        return Ok(());
+    }
+    let author = message.author.as_ref().map(|author| author.id);
+    if let Some(entry) =
+        framework
+            .data
+            .blocklist
+            .check(author, message.guild_id, Some(message.channel_id))
+    {
+        if CONFIG.block_notice {
+            let reply = Reply::Message {
+                http: framework.http_client(),
+                channel_id: message.channel_id,
+                message_id: Id::<MessageMarker>::from_str(&existing_execution.reply_id)?,
+            };
+            return reject(&framework, message.id, &reply, &entry.notice()).await;
+        }
+
+        return Ok(());
+    }
 
-    let Some(code) = crate::parsers::match_code(&content, false) else {
+    // events may be handled out of order, an older edit must not replace the output of a newer
+    let version = message
+        .edited_timestamp
+        .map_or(0, |edited_timestamp| edited_timestamp.as_micros());
+    let edited_at = OffsetDateTime::from_unix_timestamp_nanos(i128::from(version) * 1000)?;
+    if !claim_edit(&framework, existing_execution.id, edited_at).await? {
         return Ok(());
+    }
+
+    let reply = Reply::Message {
+        http: framework.http_client(),
+        channel_id: message.channel_id,
+        message_id: Id::<MessageMarker>::from_str(&existing_execution.reply_id)?,
     };
 
+    let prefixed = parsers::replace_prefix(content, &settings.prefix);
+    let Some(code) = prefixed
+        .as_deref()
+        .and_then(|prefixed| parsers::match_code(prefixed, false))
+    else {
+        // the output of code that isn't there anymore goes with it
+        return delete_replies(&framework, &[message.id]).await;
+    };
     tracing::info!("matched code: {code:#?}");
-    let Some(language) = Languages::from_codeblock_language(code.language) else {
-        if let Err(e) = framework
-            .http_client()
-            .create_message(message.channel_id)
-            .reply(message.id)
-            .content(&format!(
-                "Unsupported language `{language}`",
-                language = code.language
-            ))
-            .await
-        {
-            tracing::error!("failed to reply to message - {e}");
-        }
 
-        return Ok(());
+    let (language, options) = match execution::check(&code, &settings) {
+        Ok(checked) => checked,
+        Err(rejection) => {
+            return reject(&framework, message.id, &reply, &rejection.to_string()).await
+        }
     };
     tracing::info!("matched language: {language:#?}");
 
-    let code_result = match framework.data.hypervisor.exec(&language, code.code).await {
-        Ok(res) => res,
-        Err(e) => {
-            if let Err(e) = framework
-                .http_client()
-                .create_message(message.channel_id)
-                .reply(message.id)
-                .content(&format!("Failed to execute code: {e}"))
-                .await
-            {
-                tracing::error!("failed to reply to message - {e}");
-            }
-
-            return Ok(());
-        }
+    // supersedes the previous run if it's still going
+    let Some(running) = framework
+        .data
+        .running
+        .start_version(message.id.cast(), version)
+    else {
+        return Ok(());
     };
+    // further edits in quick succession only run the last one
+    tokio::select! {
+        _ = tokio::time::sleep(Duration::from_millis(CONFIG.edit_debounce_ms)) => {}
+        _ = running.token().cancelled() => return Ok(()),
+    }
+    if !claim_edit(&framework, existing_execution.id, edited_at).await? {
+        return Ok(());
+    }
+    if let Err(rejection) = execution::acquire(&framework.data, author, message.guild_id).await? {
+        return reject(&framework, message.id, &reply, &rejection.to_string()).await;
+    }
 
-    let out = format_output(code_result);
+    let language_name = language.to_string();
+    sqlx::query!(
+        "update execution set code = $1, language = $2, runs = runs + 1, last_run_at = now() where id = $3",
+        content,
+        language_name,
+        existing_execution.id
+    )
+    .execute(&framework.data.db)
+    .await?;
 
-    let reply_id = Id::<MessageMarker>::from_str(&existing_execution.reply_id).unwrap();
-    if let Err(e) = framework
-        .http_client()
-        .update_message(message.channel_id, reply_id)
-        .content(Some(&format!(
-            "```{language}\n{out}\n```\n-# ℹ️ Edit your message and the output will update"
-        )))
-        .await
-    {
+    let components = message
+        .author
+        .as_ref()
+        .map(|author| vec![stop::row(running.key(), author.id)])
+        .unwrap_or_default();
+    if let Err(e) = reply.edit("-# ⏳ Running your code...", &components).await {
+        tracing::error!("failed to edit message - {e}");
+    }
+
+    let started = Instant::now();
+    let result = execute(
+        &reply,
+        &framework.data.hypervisor,
+        &language,
+        &code,
+        options,
+        running.token(),
+        &components,
+    )
+    .await;
+    execution::finish(
+        &framework.data,
+        &reply,
+        &running,
+        result,
+        started.elapsed(),
+        Finish {
+            id: existing_execution.id,
+            actions: actions_row(existing_execution.id),
+            edit_hint: true,
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Records that the reply of `execution` shows the edit made at `edited_at`.
+///
+/// Returns `false` if it already shows a newer edit.
+async fn claim_edit(
+    framework: &BotFramework,
+    execution: i64,
+    edited_at: OffsetDateTime,
+) -> anyhow::Result<bool> {
+    let claimed = sqlx::query!(
+        "update execution set edited_at = $1 where id = $2 and (edited_at is null or edited_at <= $1) returning id",
+        edited_at,
+        execution
+    )
+    .fetch_optional(&framework.data.db)
+    .await?;
+
+    Ok(claimed.is_some())
+}
+
+/// Shows why the edited code can't run in place of its previous output, stopping the previous
+/// run if it's still going.
+async fn reject(
+    framework: &BotFramework,
+    message_id: Id<MessageMarker>,
+    reply: &Reply<'_>,
+    content: &str,
+) -> anyhow::Result<()> {
+    framework.data.running.cancel(message_id.cast());
+    framework.data.pages.remove(message_id.cast());
+    if let Err(e) = reply.edit(content, &[]).await {
         tracing::error!("failed to edit message - {e}");
     }
 
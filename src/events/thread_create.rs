@@ -1,6 +1,6 @@
 use twilight_model::gateway::payload::incoming::ThreadCreate;
 
-use crate::BotFramework;
+use crate::{metrics, BotFramework};
 
 pub async fn handle(framework: BotFramework, event: Box<ThreadCreate>) -> anyhow::Result<()> {
     if event.parent_id.is_none() && event.guild_id.is_none() {
@@ -15,6 +15,7 @@ pub async fn handle(framework: BotFramework, event: Box<ThreadCreate>) -> anyhow
         Ok(_) => {}
         Err(e) => {
             tracing::error!("failed to join thread: {e:#?}");
+            metrics::record_http_error(&e);
         }
     }
 
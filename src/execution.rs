@@ -0,0 +1,175 @@
+//! The checks code goes through before it runs, and the reply it gets once it ran, shared by
+//! every way of running code.
+
+use std::time::Duration;
+
+use twilight_model::{
+    channel::message::Component,
+    id::{
+        marker::{GuildMarker, UserMarker},
+        Id,
+    },
+};
+
+use crate::{
+    components::page,
+    hypervisor::{exec_error::ExecError, languages::Languages},
+    parsers::{self, MatchedCode, OptionError, Options},
+    rate_limit,
+    reply::{record, Executed, Reply},
+    settings::GuildSettings,
+    state::{BotState, RunningExecution},
+};
+
+/// Why code isn't run, shown to whoever tried to run it.
+#[derive(thiserror::Error, Debug)]
+pub enum Rejection {
+    #[error("Unsupported language `{0}`")]
+    UnsupportedLanguage(String),
+
+    #[error("`{0}` is not allowed in this server")]
+    NotAllowed(Languages),
+
+    #[error("Invalid options: {0}")]
+    InvalidOptions(#[from] OptionError),
+
+    #[error("{}", rate_limit::retry_message(*.0))]
+    RateLimited(Duration),
+}
+
+/// Finds the language called `name` in a code block, if it's enabled and allowed by the guild.
+pub fn language(name: &str, settings: &GuildSettings) -> Result<Languages, Rejection> {
+    let language = Languages::from_codeblock_language(name)
+        .filter(Languages::enabled)
+        .ok_or_else(|| Rejection::UnsupportedLanguage(name.to_string()))?;
+    if !settings.allows_language(&language) {
+        return Err(Rejection::NotAllowed(language));
+    }
+
+    Ok(language)
+}
+
+/// Finds the language of `code` with [`language`] and parses the options given with it,
+/// filling in the defaults of the guild.
+pub fn check(
+    code: &MatchedCode<'_>,
+    settings: &GuildSettings,
+) -> Result<(Languages, Options), Rejection> {
+    let language = language(code.language, settings)?;
+    let mut options = Options::parse(code)?;
+    settings.apply(&mut options);
+
+    Ok((language, options))
+}
+
+/// Reads the code an execution ran back from the database: the message it came from, or the
+/// code and input given to `/run` on their own.
+pub fn stored_code<'a>(
+    language: &'a str,
+    message: Option<&'a str>,
+    source: Option<&'a str>,
+    stdin: Option<&'a str>,
+) -> Option<MatchedCode<'a>> {
+    match (message, source) {
+        // parsed like it is for the context menu command, which also accepts `$>` messages
+        (Some(message), _) => parsers::match_code(message, true),
+        (None, Some(source)) => Some(MatchedCode {
+            language,
+            options: "",
+            code: source,
+            stdin,
+            cases: vec![],
+        }),
+        (None, None) => None,
+    }
+}
+
+/// Takes a rate limit token for an execution by `user` in `guild`.
+pub async fn acquire(
+    state: &BotState,
+    user: Option<Id<UserMarker>>,
+    guild: Option<Id<GuildMarker>>,
+) -> anyhow::Result<Result<(), Rejection>> {
+    let wait = state.rate_limits.acquire(&state.db, user, guild).await?;
+
+    Ok(match wait {
+        Some(wait) => Err(Rejection::RateLimited(wait)),
+        None => Ok(()),
+    })
+}
+
+/// Checks that `user` may run `code` in `guild`, first with [`check`] and then with
+/// [`acquire`], returning what to run it as.
+pub async fn prepare(
+    state: &BotState,
+    code: &MatchedCode<'_>,
+    settings: &GuildSettings,
+    user: Option<Id<UserMarker>>,
+    guild: Option<Id<GuildMarker>>,
+) -> anyhow::Result<Result<(Languages, Options), Rejection>> {
+    let (language, options) = match check(code, settings) {
+        Ok(checked) => checked,
+        Err(rejection) => return Ok(Err(rejection)),
+    };
+    if let Err(rejection) = acquire(state, user, guild).await? {
+        return Ok(Err(rejection));
+    }
+
+    Ok(Ok((language, options)))
+}
+
+/// What the reply of a finished execution shows besides its output.
+pub struct Finish {
+    /// The id of the execution in the database
+    pub id: i64,
+    /// The buttons below the output
+    pub actions: Component,
+    /// Whether the reply tells that editing the message updates the output
+    pub edit_hint: bool,
+}
+
+/// Records how the execution ended and shows its result in `reply`.
+///
+/// An execution that was cancelled is left alone, whoever cancelled it takes care of the reply,
+/// unless it was stopped through its Stop button.
+pub async fn finish(
+    state: &BotState,
+    reply: &Reply<'_>,
+    running: &RunningExecution,
+    result: Result<Executed, ExecError>,
+    duration: Duration,
+    finish: Finish,
+) -> anyhow::Result<()> {
+    record(&state.db, finish.id, &result, duration).await?;
+
+    let (content, attachments, mut components) = match result {
+        Ok(mut executed) => {
+            if finish.edit_hint {
+                executed.push_line("-# ℹ️ Edit your message and the output will update");
+            }
+            let components = page::store(&state.pages, running.key(), executed.paged);
+
+            (executed.content, executed.attachments, components)
+        }
+        // the Stop button leaves the reply to the execution, which knows the buttons it needs
+        Err(ExecError::Cancelled) if running.stopped() => {
+            ("-# ⏹️ Execution stopped".to_string(), vec![], vec![])
+        }
+        Err(ExecError::Cancelled) => return Ok(()),
+        Err(e) => {
+            tracing::error!("failed to execute code - {e:#?}");
+
+            (format!("Failed to execute code: {e}"), vec![], vec![])
+        }
+    };
+    components.push(finish.actions);
+
+    if let Err(e) = reply
+        .edit_with_attachments(&content, &components, &attachments)
+        .await
+    {
+        tracing::error!("failed to edit reply - {e}");
+    }
+
+    Ok(())
+}
@@ -1,3 +1,6 @@
+use asm::{AsmOptions, Emit};
+use bench::{BenchReport, BenchRun};
+use check::CheckReport;
 use docker_api::{
     conn::TtyChunk,
     opts::{
@@ -6,16 +9,46 @@ use docker_api::{
     Container, Docker as DockerClient,
 };
 use exec_error::ExecError;
-use futures::StreamExt;
-use languages::Languages;
+use exit_status::ExitStatus;
+use futures::{
+    channel::{mpsc, oneshot},
+    stream::BoxStream,
+    Stream, StreamExt,
+};
+use judge::{CaseResult, JudgeReport, TestCase, Verdict};
+use languages::{Checker, Languages};
 use rand::Rng;
+use std::pin::Pin;
 use std::str;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 use std::{env, sync::Arc};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::{CancellationToken, DropGuard};
+use usage::{CgroupSample, ResourceUsage};
 
 use crate::config::CONFIG;
+use crate::metrics::{self, ExecutionGauge, Outcome};
+use crate::render;
 
+pub mod ansi;
+pub mod asm;
+pub mod bench;
+pub mod check;
 pub mod exec_error;
+pub mod exit_status;
+pub mod judge;
 pub mod languages;
+pub mod usage;
+
+/// How long a program may run before it is killed.
+const EXEC_TIMEOUT: Duration = Duration::from_secs(10);
+/// The file in the working directory that is fed to programs through stdin.
+const STDIN_FILE: &str = "stdin.txt";
+/// The file in the working directory `run.sh`'s exit code is written to.
+const EXIT_CODE_FILE: &str = ".exit_code";
+/// How many characters of a formatter's error are kept.
+const MAX_FORMAT_ERROR: usize = 1500;
 
 macro_rules! exec_options {
     ($command:expr, $($arg:expr),*) => {
@@ -27,15 +60,46 @@ macro_rules! exec_options {
     };
 }
 
+/// How to run a program, besides its code.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RunOptions<'a> {
+    /// Fed to the program through stdin
+    pub stdin: Option<&'a str>,
+    /// Whether compilers should print colored diagnostics
+    pub color: bool,
+}
+
 pub struct Hypervisor {
     client: Arc<DockerClient>,
+    /// One permit for every container that may run at once
+    slots: Arc<Semaphore>,
 }
 
 impl Hypervisor {
     pub fn new(endpoint: String) -> Self {
         let client = Arc::new(DockerClient::new(endpoint).expect("failed to create docker client"));
+        let slots = Arc::new(Semaphore::new(CONFIG.max_concurrent_executions.max(1)));
 
-        Self { client }
+        Self { client, slots }
+    }
+
+    /// Waits until fewer than the configured number of containers are running, counting the
+    /// execution as queued meanwhile.
+    ///
+    /// The container may run for as long as the returned slot is alive.
+    async fn slot(&self, cancel: &CancellationToken) -> Result<Slot, ExecError> {
+        let _queued = ExecutionGauge::queued();
+        let permit = tokio::select! {
+            permit = self.slots.clone().acquire_owned() => {
+                permit.expect("container slots are never closed")
+            }
+            _ = cancel.cancelled() => return Err(ExecError::Cancelled),
+        };
+
+        Ok(Slot {
+            _permit: permit,
+            _running: ExecutionGauge::running(),
+        })
     }
 
     pub async fn init(&self) -> docker_api::errors::Result<()> {
@@ -91,9 +155,11 @@ impl Hypervisor {
             .tag(format!("run.sh_{language}:latest"))
             .build();
 
+        let started = Instant::now();
         let images = self.client.images();
         let mut stream = images.build(&opts);
         while (stream.next().await).is_some() {}
+        metrics::record_image_build(language, started.elapsed());
 
         Ok(())
     }
@@ -104,8 +170,13 @@ impl Hypervisor {
         language: &Languages,
         id: u32,
     ) -> docker_api::errors::Result<Container> {
+        let started = Instant::now();
         let container = self.create_container(language, id).await?;
+        metrics::record_step("create", started.elapsed());
+
+        let started = Instant::now();
         self.start_container(language, id).await?;
+        metrics::record_step("start", started.elapsed());
 
         Ok(container)
     }
@@ -165,98 +236,801 @@ impl Hypervisor {
         self.client.containers().create(&opts).await
     }
 
-    #[tracing::instrument(level = "debug", skip(self, code))]
-    pub async fn exec(&self, language: &Languages, code: &str) -> Result<Vec<Vec<u8>>, ExecError> {
-        let id = rand::thread_rng().gen_range(u32::MIN..u32::MAX);
-        let dir = format!("/tmp/eval/{id}");
-        let container_name = format!("run.sh_{language}_{id}");
-        tracing::debug!("container name: {container_name}");
-        self.run(language, id).await?;
-        let container = self.client.containers().get(&container_name);
-
-        tracing::debug!("creating unique folder in container");
-        let mut stream = container
-            .exec(&exec_options!("mkdir", "-p", &dir), &Default::default())
-            .await?;
-        if let Some(chunk) = stream.next().await {
-            let chunk = chunk?;
-            tracing::debug!("stdout: {chunk:#?}");
+    /// Executes `code` in a fresh container and collects its output.
+    ///
+    /// Cancelling `cancel` kills the container and makes this return [`ExecError::Cancelled`].
+    pub async fn exec(
+        &self,
+        language: &Languages,
+        code: &str,
+        options: RunOptions<'_>,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<TtyChunk>, ExecError> {
+        let mut stream = self.exec_stream(language, code, options, cancel).await?;
+        let mut res = vec![];
+        while let Some(chunk) = stream.next().await {
+            res.push(chunk?);
         }
 
-        tracing::debug!("chmoding folder");
-        let mut stream = container
-            .exec(&exec_options!("chmod", "777", &dir), &Default::default())
-            .await?;
-        while (stream.next().await).is_some() {}
+        Ok(res)
+    }
 
-        // execute code in container
-        tracing::debug!("executing code in container");
-        let options = ExecCreateOpts::builder()
-            .command(["/bin/sh", "/var/run/run.sh", code])
-            .user("1001:1001")
-            .working_dir(dir)
-            .attach_stdout(true)
-            .attach_stderr(true)
-            .build();
+    /// Executes `code` in a fresh container, yielding its output while it runs.
+    ///
+    /// The stream ends with an error if the execution fails, times out, runs out of memory or is
+    /// cancelled through `cancel`. Dropping the stream cancels the execution.
+    #[tracing::instrument(level = "debug", skip(self, code, options, cancel))]
+    pub async fn exec_stream(
+        &self,
+        language: &Languages,
+        code: &str,
+        options: RunOptions<'_>,
+        cancel: &CancellationToken,
+    ) -> Result<ExecStream, ExecError> {
+        let cancel = cancel.child_token();
+        let spawned = async {
+            let slot = self.slot(&cancel).await?;
+            let spawned = self.spawn_program(language, code, options, &cancel).await?;
 
-        let mut stream = container.exec(&options, &Default::default()).await?;
-        let mut res = vec![];
-        let timeout = tokio::time::sleep(std::time::Duration::from_secs(10));
-        tokio::pin!(timeout);
-
-        loop {
-            tokio::select! {
-                _ = &mut timeout => return Err(ExecError::Timeout),
-                chunk = stream.next() => match chunk {
-                    Some(Ok(TtyChunk::StdOut(bytes) | TtyChunk::StdErr(bytes))) => {
-                        res.push(bytes);
-                    },
-                    Some(Ok(TtyChunk::StdIn(_))) => unreachable!(),
-                    Some(Err(e)) => {
-                        // kill container
-                        tracing::debug!("killing container");
-                        container.kill(None).await?;
-
-                        return Err(ExecError::DockerConnection(e));
-                    },
-                    None => break,
+            Ok::<_, ExecError>((slot, spawned))
+        };
+        let (slot, spawned) = match spawned.await {
+            Ok(spawned) => spawned,
+            Err(e) => {
+                metrics::record_execution(language, Outcome::of_error(&e));
+                return Err(e);
+            }
+        };
+
+        let (tx, chunks) = mpsc::unbounded();
+        let (finished_tx, finished) = oneshot::channel();
+        tokio::spawn({
+            let language = language.clone();
+            let cancel = cancel.clone();
+            async move {
+                let Spawned {
+                    container,
+                    dir,
+                    before,
+                    output,
+                } = spawned;
+                let result = forward_output(output, EXEC_TIMEOUT, &cancel, |chunk| {
+                    let _ = tx.unbounded_send(Ok(chunk));
+                })
+                .await;
+                let result = match result {
+                    Ok(()) => finish_program(&container, &dir, before, language.builds()).await,
+                    Err(e) => Err(e),
+                };
+
+                tracing::debug!("killing container");
+                let result = match container.kill(None).await {
+                    Ok(()) => result,
+                    Err(e) => result.and(Err(e.into())),
+                };
+                let outcome = match &result {
+                    Ok(Finished {
+                        status: Some(ExitStatus::CompileFailure),
+                        ..
+                    }) => Outcome::CompileFailure,
+                    result => Outcome::of(result),
+                };
+                metrics::record_execution(&language, outcome);
+                drop(slot);
+
+                match result {
+                    Ok(result) => {
+                        let _ = finished_tx.send(result);
+                    }
+                    Err(e) => {
+                        let _ = tx.unbounded_send(Err(e));
+                    }
                 }
             }
+        });
+
+        Ok(ExecStream {
+            chunks,
+            finished,
+            _cancel: cancel.drop_guard(),
+        })
+    }
+
+    /// Executes `code` once to build it, then runs the built program `runs` more times in the
+    /// same container while measuring each run.
+    ///
+    /// Measuring stops early once the configured time budget is used up, and as soon as the
+    /// program fails to build or a run exits unsuccessfully.
+    #[tracing::instrument(level = "debug", skip(self, code, cancel))]
+    pub async fn bench(
+        &self,
+        language: &Languages,
+        code: &str,
+        runs: usize,
+        cancel: &CancellationToken,
+    ) -> Result<BenchReport, ExecError> {
+        let Some(run_command) = language.run_command() else {
+            return Err(ExecError::Unsupported("benchmarking"));
+        };
+
+        let id = rand::thread_rng().gen_range(u32::MIN..u32::MAX);
+        let dir = format!("/tmp/eval/{id}");
+        tracing::debug!("container name: run.sh_{language}_{id}");
+        let container = async {
+            let slot = self.slot(cancel).await?;
+            let container = self.run(language, id).await?;
+
+            Ok::<_, ExecError>((slot, container))
+        };
+        let (_slot, container) = match container.await {
+            Ok(container) => container,
+            Err(e) => {
+                metrics::record_execution(language, Outcome::of_error(&e));
+                return Err(e);
+            }
+        };
+
+        let result = bench_program(
+            &container,
+            &dir,
+            code,
+            run_command,
+            language.builds(),
+            runs,
+            cancel,
+        )
+        .await;
+
+        tracing::debug!("killing container");
+        let result = match container.kill(None).await {
+            Ok(()) => result,
+            Err(e) => result.and(Err(e.into())),
+        };
+        let outcome = match &result {
+            Ok(BenchReport {
+                status: Some(ExitStatus::CompileFailure),
+                ..
+            }) => Outcome::CompileFailure,
+            result => Outcome::of(result),
+        };
+        metrics::record_execution(language, outcome);
+
+        result
+    }
+
+    /// Formats `code` with the formatter of `language`, returning the formatted code.
+    #[tracing::instrument(level = "debug", skip(self, code, cancel))]
+    pub async fn format(
+        &self,
+        language: &Languages,
+        code: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Vec<u8>>, ExecError> {
+        let Some(format_command) = language.format_command() else {
+            return Err(ExecError::Unsupported("formatting"));
+        };
+
+        let _slot = self.slot(cancel).await?;
+        let id = rand::thread_rng().gen_range(u32::MIN..u32::MAX);
+        let dir = format!("/tmp/eval/{id}");
+        tracing::debug!("container name: run.sh_{language}_{id}");
+        let container = self.run(language, id).await?;
+
+        let result = format_program(&container, &dir, code, format_command, cancel).await;
+        kill_best_effort(&container).await;
+
+        result
+    }
+
+    /// Runs the static checker of `language` on `code` without running it.
+    #[tracing::instrument(level = "debug", skip(self, code, cancel))]
+    pub async fn check(
+        &self,
+        language: &Languages,
+        code: &str,
+        cancel: &CancellationToken,
+    ) -> Result<CheckReport, ExecError> {
+        let Some(checker) = language.checker() else {
+            return Err(ExecError::Unsupported("checking"));
+        };
+
+        let _slot = self.slot(cancel).await?;
+        let id = rand::thread_rng().gen_range(u32::MIN..u32::MAX);
+        let dir = format!("/tmp/eval/{id}");
+        tracing::debug!("container name: run.sh_{language}_{id}");
+        let container = self.run(language, id).await?;
+
+        let result = check_program(&container, &dir, code, checker, cancel).await;
+        kill_best_effort(&container).await;
+
+        result
+    }
+
+    /// Compiles `code` and returns what the compiler emitted instead of running it.
+    ///
+    /// Assembly is filtered of directives and comments, and symbols are demangled where the
+    /// toolchain allows it.
+    #[tracing::instrument(level = "debug", skip(self, code, cancel))]
+    pub async fn asm(
+        &self,
+        language: &Languages,
+        code: &str,
+        options: &AsmOptions,
+        cancel: &CancellationToken,
+    ) -> Result<String, ExecError> {
+        let Some((file, command)) = language.asm_command(options) else {
+            return Err(ExecError::Unsupported(options.emit.name()));
+        };
+
+        let _slot = self.slot(cancel).await?;
+        let id = rand::thread_rng().gen_range(u32::MIN..u32::MAX);
+        let dir = format!("/tmp/eval/{id}");
+        tracing::debug!("container name: run.sh_{language}_{id}");
+        let container = self.run(language, id).await?;
+
+        let result = compile_program(&container, &dir, code, file, &command, cancel).await;
+        kill_best_effort(&container).await;
+
+        let output = result?;
+        // go prints plan 9 assembly, which has no directives to speak of
+        if options.emit == Emit::Asm && *language != Languages::Golang {
+            Ok(asm::filter_directives(&output))
+        } else {
+            Ok(output)
         }
+    }
+
+    /// Executes `code` with the input of every test case and compares its stdout to the expected
+    /// output, like an online judge.
+    ///
+    /// The program is built once with the first case if the language allows rerunning it. Judging
+    /// stops if the build fails and at the first case that exceeds the time limit.
+    #[tracing::instrument(level = "debug", skip(self, code, cases, cancel))]
+    pub async fn judge(
+        &self,
+        language: &Languages,
+        code: &str,
+        cases: &[TestCase<'_>],
+        cancel: &CancellationToken,
+    ) -> Result<JudgeReport, ExecError> {
+        let id = rand::thread_rng().gen_range(u32::MIN..u32::MAX);
+        let dir = format!("/tmp/eval/{id}");
+        tracing::debug!("container name: run.sh_{language}_{id}");
+        let container = async {
+            let slot = self.slot(cancel).await?;
+            let container = self.run(language, id).await?;
+
+            Ok::<_, ExecError>((slot, container))
+        };
+        let (_slot, container) = match container.await {
+            Ok(container) => container,
+            Err(e) => {
+                metrics::record_execution(language, Outcome::of_error(&e));
+                return Err(e);
+            }
+        };
+
+        let result = judge_program(
+            &container,
+            &dir,
+            code,
+            language.run_command(),
+            cases,
+            cancel,
+        )
+        .await;
 
-        // kill container
         tracing::debug!("killing container");
-        container.kill(None).await?;
+        let result = match container.kill(None).await {
+            Ok(()) => result,
+            Err(e) => result.and(Err(e.into())),
+        };
+        let outcome = match &result {
+            Ok(report) if report.compile_output.is_some() => Outcome::CompileFailure,
+            result => Outcome::of(result),
+        };
+        metrics::record_execution(language, outcome);
 
-        Ok(res)
+        result
     }
+
+    /// Creates a container for `language` and starts `code` in it, sampling the resource
+    /// counters of the container right before.
+    async fn spawn_program(
+        &self,
+        language: &Languages,
+        code: &str,
+        options: RunOptions<'_>,
+        cancel: &CancellationToken,
+    ) -> Result<Spawned, ExecError> {
+        let id = rand::thread_rng().gen_range(u32::MIN..u32::MAX);
+        let dir = format!("/tmp/eval/{id}");
+        tracing::debug!("container name: run.sh_{language}_{id}");
+        let container = self.run(language, id).await?;
+
+        let started = async {
+            let before = sample_usage(&container).await?;
+            let output = start_program(&container, &dir, code, options, cancel).await?;
+
+            Ok::<_, ExecError>((before, output))
+        };
+        match started.await {
+            Ok((before, output)) => Ok(Spawned {
+                container,
+                dir,
+                before,
+                output,
+            }),
+            Err(e) => {
+                tracing::debug!("killing container");
+                container.kill(None).await?;
+
+                Err(e)
+            }
+        }
+    }
+}
+
+type ChunkResult = Result<TtyChunk, docker_api::conn::Error>;
+
+/// A container slot taken by [`Hypervisor::slot`], given back when dropped.
+struct Slot {
+    _permit: OwnedSemaphorePermit,
+    _running: ExecutionGauge,
+}
+
+/// A program started by [`Hypervisor::spawn_program`].
+struct Spawned {
+    container: Container,
+    dir: String,
+    /// The resource counters of the container right before the program started
+    before: CgroupSample,
+    output: BoxStream<'static, ChunkResult>,
 }
 
-/// Formats the output of a code execution for Discord.
+/// How a program that ran to completion finished.
+#[derive(Debug, Clone, Copy)]
+pub struct Finished {
+    /// How `run.sh` exited, if it got to report it
+    pub status: Option<ExitStatus>,
+    pub usage: ResourceUsage,
+}
+
+/// The output of a running program, see [`Hypervisor::exec_stream`].
+pub struct ExecStream {
+    chunks: mpsc::UnboundedReceiver<Result<TtyChunk, ExecError>>,
+    finished: oneshot::Receiver<Finished>,
+    _cancel: DropGuard,
+}
+
+impl ExecStream {
+    /// How the program finished, once the stream has ended without an error.
+    pub async fn finished(self) -> Option<Finished> {
+        self.finished.await.ok()
+    }
+}
+
+impl Stream for ExecStream {
+    type Item = Result<TtyChunk, ExecError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.chunks.poll_next_unpin(cx)
+    }
+}
+
+/// Creates the working directory of an execution.
+/// Kills `container` once what ran in it is done, only logging a failure so it doesn't throw
+/// away the result.
+async fn kill_best_effort(container: &Container) {
+    tracing::debug!("killing container");
+    if let Err(e) = container.kill(None).await {
+        tracing::error!("failed to kill container - {e}");
+    }
+}
+
+async fn prepare_dir(container: &Container, dir: &str) -> Result<(), ExecError> {
+    tracing::debug!("creating unique folder in container");
+    let mut stream = container
+        .exec(&exec_options!("mkdir", "-p", dir), &Default::default())
+        .await?;
+    if let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        tracing::debug!("stdout: {chunk:#?}");
+    }
+
+    tracing::debug!("chmoding folder");
+    let mut stream = container
+        .exec(&exec_options!("chmod", "777", dir), &Default::default())
+        .await?;
+    while (stream.next().await).is_some() {}
+
+    Ok(())
+}
+
+/// Starts `command` as the unprivileged user inside `dir`.
+async fn start_command(
+    container: &Container,
+    dir: &str,
+    command: &[&str],
+) -> Result<BoxStream<'static, ChunkResult>, ExecError> {
+    let options = ExecCreateOpts::builder()
+        .command(command)
+        .user("1001:1001")
+        .working_dir(dir)
+        .attach_stdout(true)
+        .attach_stderr(true)
+        .build();
+
+    Ok(container.exec(&options, &Default::default()).await?.boxed())
+}
+
+/// Writes `contents` to the file `name` in `dir`, as the unprivileged user.
+async fn write_file(
+    container: &Container,
+    dir: &str,
+    name: &str,
+    contents: &str,
+) -> Result<(), ExecError> {
+    let command = [
+        "/bin/sh",
+        "-c",
+        r#"printf %s "$1" > "$2""#,
+        "sh",
+        contents,
+        name,
+    ];
+    let mut stream = start_command(container, dir, &command).await?;
+    while let Some(chunk) = stream.next().await {
+        chunk?;
+    }
+
+    Ok(())
+}
+
+async fn start_program(
+    container: &Container,
+    dir: &str,
+    code: &str,
+    options: RunOptions<'_>,
+    cancel: &CancellationToken,
+) -> Result<BoxStream<'static, ChunkResult>, ExecError> {
+    prepare_dir(container, dir).await?;
+
+    if cancel.is_cancelled() {
+        return Err(ExecError::Cancelled);
+    }
+
+    let redirect = match options.stdin {
+        Some(stdin) => {
+            write_file(container, dir, STDIN_FILE, stdin).await?;
+            format!(" < {STDIN_FILE}")
+        }
+        None => String::new(),
+    };
+    // see languages/README.md
+    let env = if options.color { "RUNSH_COLOR=1 " } else { "" };
+
+    // execute code in container
+    tracing::debug!("executing code in container");
+    let command =
+        format!(r#"{env}/bin/sh /var/run/run.sh "$1"{redirect}; echo $? > {EXIT_CODE_FILE}"#);
+    start_command(container, dir, &["/bin/sh", "-c", &command, "sh", code]).await
+}
+
+/// Collects how a program that ran to completion finished, failing if it ran out of memory.
 ///
-/// - Applies a truncation of 1500 characters if the output is longer than that.
-/// - If the output is empty, returns "No output".
-pub fn format_output(code_result: Vec<Vec<u8>>) -> String {
-    let out = code_result
-        .iter()
-        .map(|b| String::from_utf8_lossy(b))
-        .collect::<Vec<_>>()
-        .join("\n");
-
-    let out = if out.len() > 1500 {
-        let trunc = out.len() - 1500;
-        let mut out = out;
-        out.truncate(1500);
-        out.push_str(&format!("...({trunc} more characters)"));
-
-        out
-    } else {
-        out
+/// `includes_build` tells whether `run.sh` built the program first, which the usage then
+/// includes.
+async fn finish_program(
+    container: &Container,
+    dir: &str,
+    before: CgroupSample,
+    includes_build: bool,
+) -> Result<Finished, ExecError> {
+    let mut usage = ResourceUsage::between(before, sample_usage(container).await?);
+    usage.includes_build = includes_build;
+    if usage.oom_killed {
+        return Err(ExecError::OutOfMemory);
+    }
+
+    let status = read_exit_status(container, dir).await?;
+
+    Ok(Finished { status, usage })
+}
+
+/// Reads how the last program started by [`start_program`] in `dir` exited, if it got to
+/// report it.
+async fn read_exit_status(
+    container: &Container,
+    dir: &str,
+) -> Result<Option<ExitStatus>, ExecError> {
+    let path = format!("{dir}/{EXIT_CODE_FILE}");
+    let mut stream = container
+        .exec(&exec_options!("cat", path.as_str()), &Default::default())
+        .await?;
+    let mut output = vec![];
+    while let Some(chunk) = stream.next().await {
+        if let TtyChunk::StdOut(bytes) = chunk? {
+            output.extend(bytes);
+        }
+    }
+
+    Ok(String::from_utf8_lossy(&output)
+        .trim()
+        .parse()
+        .ok()
+        .map(ExitStatus::from_code))
+}
+
+async fn bench_program(
+    container: &Container,
+    dir: &str,
+    code: &str,
+    run_command: &str,
+    includes_build: bool,
+    runs: usize,
+    cancel: &CancellationToken,
+) -> Result<BenchReport, ExecError> {
+    let budget = Duration::from_secs(CONFIG.bench_time_budget_secs);
+    let deadline = Instant::now() + budget;
+
+    let output = start_program(container, dir, code, RunOptions::default(), cancel).await?;
+    let mut first_output = vec![];
+    forward_output(output, budget.min(EXEC_TIMEOUT), cancel, |chunk| {
+        first_output.push(chunk);
+    })
+    .await?;
+
+    let mut report = BenchReport {
+        output: first_output,
+        status: read_exit_status(container, dir).await?,
+        requested: runs,
+        runs: Vec::with_capacity(runs),
+        peak_memory: 0,
+        includes_build,
     };
+    // a program that doesn't build or fails isn't worth measuring
+    if report.status != Some(ExitStatus::Success) {
+        return Ok(report);
+    }
+
+    let command = format!("{run_command}; echo $? > {EXIT_CODE_FILE}");
+    for run in 0..runs {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+
+        tracing::debug!("starting benchmark run {run}");
+        let before = sample_usage(container).await?;
+        let started = Instant::now();
+        let output = start_command(container, dir, &["/bin/sh", "-c", &command]).await?;
+        match forward_output(output, remaining.min(EXEC_TIMEOUT), cancel, |_| {}).await {
+            Ok(()) => {}
+            // the time budget ran out, report the runs we have
+            Err(ExecError::Timeout) if remaining < EXEC_TIMEOUT => break,
+            Err(e) => return Err(e),
+        }
+        let wall_time = started.elapsed();
+        let after = sample_usage(container).await?;
+
+        report.status = read_exit_status(container, dir).await?;
+        if report.status != Some(ExitStatus::Success) {
+            break;
+        }
+
+        report.runs.push(BenchRun {
+            wall_time,
+            cpu_time: after.cpu_time.saturating_sub(before.cpu_time),
+        });
+    }
+    report.peak_memory = sample_usage(container).await?.peak_memory;
+
+    Ok(report)
+}
+
+async fn judge_program(
+    container: &Container,
+    dir: &str,
+    code: &str,
+    run_command: Option<&str>,
+    cases: &[TestCase<'_>],
+    cancel: &CancellationToken,
+) -> Result<JudgeReport, ExecError> {
+    let mut report = JudgeReport {
+        cases: Vec::with_capacity(cases.len()),
+        total: cases.len(),
+        compile_output: None,
+    };
+    for (i, case) in cases.iter().enumerate() {
+        tracing::debug!("judging case {i}");
+        let started = Instant::now();
+        let output = match run_command {
+            Some(run_command) if i > 0 => {
+                write_file(container, dir, STDIN_FILE, case.input).await?;
+                let command = format!("{run_command} < {STDIN_FILE}");
+                start_command(container, dir, &["/bin/sh", "-c", &command]).await?
+            }
+            // the first case also builds the program
+            _ => {
+                let options = RunOptions {
+                    stdin: Some(case.input),
+                    ..RunOptions::default()
+                };
+                start_program(container, dir, code, options, cancel).await?
+            }
+        };
+
+        let mut stdout = vec![];
+        let mut all = vec![];
+        let result = forward_output(output, EXEC_TIMEOUT, cancel, |chunk| {
+            // compiler diagnostics and the like go to stderr and aren't judged
+            if let TtyChunk::StdOut(bytes) = &chunk {
+                stdout.extend(bytes);
+            }
+            if i == 0 {
+                all.extend(chunk_bytes(chunk));
+            }
+        })
+        .await;
+
+        // the program can't be judged if it didn't build
+        if i == 0
+            && result.is_ok()
+            && read_exit_status(container, dir).await? == Some(ExitStatus::CompileFailure)
+        {
+            report.compile_output = Some(String::from_utf8_lossy(&all).into_owned());
+            return Ok(report);
+        }
+
+        let output = String::from_utf8_lossy(&stdout).into_owned();
+        let verdict = match result {
+            Ok(()) if judge::output_matches(&output, case.expected) => Verdict::Accepted,
+            Ok(()) => Verdict::WrongAnswer,
+            Err(ExecError::Timeout) => Verdict::TimeLimitExceeded,
+            Err(e) => return Err(e),
+        };
+        report.cases.push(CaseResult {
+            verdict,
+            output,
+            wall_time: started.elapsed(),
+        });
+
+        // the program is still running, later cases would only compete with it
+        if verdict == Verdict::TimeLimitExceeded {
+            break;
+        }
+    }
+
+    Ok(report)
+}
+
+async fn format_program(
+    container: &Container,
+    dir: &str,
+    code: &str,
+    format_command: &str,
+    cancel: &CancellationToken,
+) -> Result<Vec<Vec<u8>>, ExecError> {
+    prepare_dir(container, dir).await?;
+    write_file(container, dir, STDIN_FILE, code).await?;
+
+    let command = format!("{format_command} < {STDIN_FILE}");
+    let output = start_command(container, dir, &["/bin/sh", "-c", &command]).await?;
+    let (mut stdout, mut stderr) = (vec![], vec![]);
+    forward_output(output, EXEC_TIMEOUT, cancel, |chunk| match chunk {
+        TtyChunk::StdOut(bytes) => stdout.push(bytes),
+        TtyChunk::StdIn(bytes) | TtyChunk::StdErr(bytes) => stderr.push(bytes),
+    })
+    .await?;
+
+    // formatters print nothing to stdout when they can't parse the code
+    if stdout.iter().all(Vec::is_empty) {
+        let stderr = stderr.concat();
+        let stderr = String::from_utf8_lossy(&stderr);
+        // the error is shown in a message, which long errors wouldn't fit into
+        let (shown, rest) = render::truncate(stderr.trim_end(), MAX_FORMAT_ERROR);
+        let stderr = if rest == 0 {
+            shown.to_string()
+        } else {
+            format!("{shown}...({rest} more characters)")
+        };
+
+        return Err(ExecError::Format(stderr));
+    }
+
+    Ok(stdout)
+}
+
+async fn check_program(
+    container: &Container,
+    dir: &str,
+    code: &str,
+    checker: Checker,
+    cancel: &CancellationToken,
+) -> Result<CheckReport, ExecError> {
+    prepare_dir(container, dir).await?;
+    write_file(container, dir, checker.file, code).await?;
+
+    let output = start_command(container, dir, &["/bin/sh", "-c", checker.command]).await?;
+    let mut out = vec![];
+    forward_output(output, EXEC_TIMEOUT, cancel, |chunk| {
+        out.extend(chunk_bytes(chunk));
+    })
+    .await?;
+
+    Ok(CheckReport::new(String::from_utf8_lossy(&out).into_owned()))
+}
+
+async fn compile_program(
+    container: &Container,
+    dir: &str,
+    code: &str,
+    file: &str,
+    command: &str,
+    cancel: &CancellationToken,
+) -> Result<String, ExecError> {
+    prepare_dir(container, dir).await?;
+    write_file(container, dir, file, code).await?;
+
+    let output = start_command(container, dir, &["/bin/sh", "-c", command]).await?;
+    let mut out = vec![];
+    forward_output(output, EXEC_TIMEOUT, cancel, |chunk| {
+        out.extend(chunk_bytes(chunk));
+    })
+    .await?;
+
+    Ok(String::from_utf8_lossy(&out).into_owned())
+}
+
+/// Reads the resource counters of `container`.
+async fn sample_usage(container: &Container) -> Result<CgroupSample, ExecError> {
+    let mut stream = container
+        .exec(
+            &exec_options!("/bin/sh", "-c", usage::SAMPLE_SCRIPT),
+            &Default::default(),
+        )
+        .await?;
+    let mut output = vec![];
+    while let Some(chunk) = stream.next().await {
+        if let TtyChunk::StdOut(bytes) = chunk? {
+            output.extend(bytes);
+        }
+    }
+
+    Ok(CgroupSample::parse(&String::from_utf8_lossy(&output)))
+}
+
+/// Forwards the output of a command to `on_chunk` until it exits, times out or is cancelled.
+async fn forward_output(
+    mut output: BoxStream<'static, ChunkResult>,
+    timeout: Duration,
+    cancel: &CancellationToken,
+    mut on_chunk: impl FnMut(TtyChunk),
+) -> Result<(), ExecError> {
+    let started = Instant::now();
+    let timeout = tokio::time::sleep(timeout);
+    tokio::pin!(timeout);
+
+    loop {
+        tokio::select! {
+            _ = &mut timeout => return Err(ExecError::Timeout),
+            _ = cancel.cancelled() => return Err(ExecError::Cancelled),
+            chunk = output.next() => match chunk {
+                Some(Ok(chunk @ (TtyChunk::StdOut(_) | TtyChunk::StdErr(_)))) => on_chunk(chunk),
+                Some(Ok(TtyChunk::StdIn(_))) => unreachable!(),
+                Some(Err(e)) => return Err(ExecError::DockerConnection(e)),
+                None => break,
+            }
+        }
+    }
+    metrics::record_step("exec", started.elapsed());
+
+    Ok(())
+}
 
-    if out.is_empty() {
-        "No output".to_string()
-    } else {
-        out
+fn chunk_bytes(chunk: TtyChunk) -> Vec<u8> {
+    match chunk {
+        TtyChunk::StdIn(bytes) | TtyChunk::StdOut(bytes) | TtyChunk::StdErr(bytes) => bytes,
     }
 }
@@ -0,0 +1,192 @@
+//! Translation of ANSI escape sequences into the subset Discord renders in ` ```ansi ` blocks.
+//!
+//! Discord only understands SGR sequences for reset (0), bold (1), underline (4) and the eight
+//! basic foreground (30-37) and background (40-47) colors. Everything else shows up as garbage,
+//! so it's either mapped onto that subset or removed.
+
+const ESC: char = '\x1b';
+
+enum Segment<'a> {
+    Text(&'a str),
+    /// An escape sequence, as the SGR codes Discord supports that it translates to
+    Sequence(Vec<u8>),
+}
+
+/// Whether `text` contains a sequence Discord can render.
+pub fn has_colors(text: &str) -> bool {
+    segments(text)
+        .iter()
+        .any(|segment| matches!(segment, Segment::Sequence(codes) if !codes.is_empty()))
+}
+
+/// Rewrites the SGR sequences of `text` into the ones Discord supports and removes all other
+/// escape sequences.
+pub fn translate(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for segment in segments(text) {
+        match segment {
+            Segment::Text(text) => out.push_str(text),
+            Segment::Sequence(codes) if codes.is_empty() => {}
+            Segment::Sequence(codes) => {
+                let codes = codes
+                    .iter()
+                    .map(u8::to_string)
+                    .collect::<Vec<_>>()
+                    .join(";");
+                out.push_str(&format!("{ESC}[{codes}m"));
+            }
+        }
+    }
+
+    out
+}
+
+/// Removes all escape sequences from `text`.
+pub fn strip(text: &str) -> String {
+    segments(text)
+        .into_iter()
+        .filter_map(|segment| match segment {
+            Segment::Text(text) => Some(text),
+            Segment::Sequence(_) => None,
+        })
+        .collect()
+}
+
+fn segments(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find(ESC) {
+        segments.push(Segment::Text(&rest[..start]));
+        let sequence = &rest[start + ESC.len_utf8()..];
+
+        let (codes, end) = match sequence.chars().next() {
+            // CSI: parameters, then a final byte in @..~
+            Some('[') => match sequence[1..].find(|c: char| ('@'..='~').contains(&c)) {
+                Some(end) if sequence[end + 1..].starts_with('m') => {
+                    (translate_sgr(&sequence[1..end + 1]), end + 2)
+                }
+                Some(end) => (vec![], end + 2),
+                None => (vec![], sequence.len()),
+            },
+            // OSC: terminated by BEL or ESC \
+            Some(']') => match sequence.find(['\x07', ESC]) {
+                Some(end) if sequence[end..].starts_with(ESC) => {
+                    // usually `\`, but whatever follows the ESC is part of the terminator
+                    let after = end + ESC.len_utf8();
+                    let terminator = sequence[after..].chars().next().map_or(0, char::len_utf8);
+                    (vec![], after + terminator)
+                }
+                Some(end) => (vec![], end + 1),
+                None => (vec![], sequence.len()),
+            },
+            Some(c) => (vec![], c.len_utf8()),
+            None => (vec![], 0),
+        };
+        segments.push(Segment::Sequence(codes));
+
+        rest = &sequence[end..];
+    }
+    segments.push(Segment::Text(rest));
+
+    segments
+}
+
+/// Maps the parameters of an SGR sequence onto the codes Discord supports.
+fn translate_sgr(params: &str) -> Vec<u8> {
+    let mut params = params
+        .split(';')
+        .map(|param| param.parse::<u16>().unwrap_or(0));
+    let mut codes = vec![];
+    while let Some(param) = params.next() {
+        match param {
+            0 | 1 | 4 | 30..=37 | 40..=47 => codes.push(param as u8),
+            // bright colors
+            90..=97 | 100..=107 => codes.push((param - 60) as u8),
+            // 256 and true colors
+            38 | 48 => {
+                let base = if param == 38 { 30 } else { 40 };
+                let color = match params.next() {
+                    Some(5) => params.next().and_then(palette_color),
+                    Some(2) => match (params.next(), params.next(), params.next()) {
+                        (Some(r), Some(g), Some(b)) => Some(rgb_color(r, g, b)),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                if let Some(color) = color {
+                    codes.push(base + color);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    codes
+}
+
+/// The basic color closest to a color of the 256 color palette.
+fn palette_color(index: u16) -> Option<u8> {
+    match index {
+        0..=7 => Some(index as u8),
+        8..=15 => Some((index - 8) as u8),
+        16..=231 => {
+            let index = index - 16;
+            let (r, g, b) = (index / 36, index / 6 % 6, index % 6);
+            Some(rgb_color(r * 51, g * 51, b * 51))
+        }
+        // grayscale
+        232..=243 => Some(0),
+        244..=255 => Some(7),
+        _ => None,
+    }
+}
+
+/// The basic color closest to a true color.
+fn rgb_color(r: u16, g: u16, b: u16) -> u8 {
+    u8::from(r > 127) | u8::from(g > 127) << 1 | u8::from(b > 127) << 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translates_supported_sgr() {
+        assert_eq!(translate("\x1b[1;31mred\x1b[0m"), "\x1b[1;31mred\x1b[0m");
+    }
+
+    #[test]
+    fn maps_bright_and_extended_colors() {
+        assert_eq!(translate("\x1b[91mx"), "\x1b[31mx");
+        assert_eq!(translate("\x1b[38;5;9mx"), "\x1b[31mx");
+        assert_eq!(translate("\x1b[48;2;0;0;255mx"), "\x1b[44mx");
+    }
+
+    #[test]
+    fn removes_unsupported_sequences() {
+        assert_eq!(translate("a\x1b[2Kb\x1b[3mc"), "abc");
+        assert_eq!(translate("\x1b]0;title\x07text"), "text");
+        assert_eq!(translate("\x1b]0;title\x1b\\text"), "text");
+    }
+
+    #[test]
+    fn handles_multibyte_after_osc_escape() {
+        assert_eq!(strip("\x1b]0;x\x1bé"), "");
+        assert_eq!(strip("\x1b]0;x\x1béa"), "a");
+    }
+
+    #[test]
+    fn handles_truncated_sequences() {
+        assert_eq!(strip("a\x1b"), "a");
+        assert_eq!(strip("a\x1b[31"), "a");
+        assert_eq!(strip("a\x1b]0;title"), "a");
+        assert_eq!(strip("a\x1bé"), "a");
+    }
+
+    #[test]
+    fn detects_colors() {
+        assert!(has_colors("\x1b[32mok"));
+        assert!(!has_colors("\x1b[2Kplain"));
+        assert!(!has_colors("plain"));
+    }
+}
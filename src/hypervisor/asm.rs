@@ -0,0 +1,164 @@
+/// What a compiler should emit instead of a program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emit {
+    /// Assembly
+    Asm,
+    /// The intermediate representation of the compiler: LLVM IR for rust, GIMPLE for c and cpp
+    /// and Core for haskell
+    Ir,
+    /// Rust's mid-level IR
+    Mir,
+}
+
+impl Emit {
+    /// A description of this output, for errors.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Emit::Asm => "assembly output",
+            Emit::Ir => "IR output",
+            Emit::Mir => "MIR output",
+        }
+    }
+
+    /// The extension of a file holding this output.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Emit::Asm => "s",
+            Emit::Ir => "ir",
+            Emit::Mir => "mir",
+        }
+    }
+
+    /// The language to highlight this output as.
+    pub fn highlight(&self) -> &'static str {
+        match self {
+            Emit::Asm => "x86asm",
+            Emit::Ir => "llvm",
+            Emit::Mir => "rust",
+        }
+    }
+}
+
+/// The assembly dialect of x86 compilers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Syntax {
+    Intel,
+    Att,
+}
+
+impl Syntax {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Syntax::Intel => "intel",
+            Syntax::Att => "att",
+        }
+    }
+}
+
+/// How to compile code for [`Hypervisor::asm`](super::Hypervisor::asm).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AsmOptions {
+    pub emit: Emit,
+    /// The optimization level, from 0 to 3
+    pub opt_level: u8,
+    pub syntax: Syntax,
+}
+
+impl Default for AsmOptions {
+    fn default() -> Self {
+        Self {
+            emit: Emit::Asm,
+            opt_level: 2,
+            syntax: Syntax::Intel,
+        }
+    }
+}
+
+/// Removes assembler directives and comments from GNU assembly, keeping labels.
+pub fn filter_directives(asm: &str) -> String {
+    asm.lines()
+        .filter(|line| {
+            let line = line.trim();
+            let directive = line.starts_with('.') && !line.ends_with(':');
+            let comment = line.starts_with('#') || line.starts_with(';');
+
+            !line.is_empty() && !directive && !comment
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hypervisor::languages::Languages;
+
+    #[test]
+    fn keeps_labels_and_instructions() {
+        let asm = "\t.file\t\"program.c\"\n\t.text\n\t.globl\tmain\n\t.type\tmain, @function\nmain:\n.LFB0:\n\t.loc 1 2 12\n\tpush\trbp\n.L2:\n\t# a comment\n\tret\n\t.size\tmain, .-main\n\n\t.ident\t\"GCC\"";
+
+        assert_eq!(
+            filter_directives(asm),
+            "main:\n.LFB0:\n\tpush\trbp\n.L2:\n\tret"
+        );
+    }
+
+    #[test]
+    fn drops_rustc_directives_and_comments() {
+        let asm = "\t.section\t.text.main,\"ax\",@progbits\n\t.p2align\t4, 0x90\nmain:\n\t.cfi_startproc\n\tpush\trax\n\t.loc\t1 1 0 prologue_end\n; %bb.0:\n\tpop\trax\n\t.cfi_endproc";
+
+        assert_eq!(filter_directives(asm), "main:\n\tpush\trax\n\tpop\trax");
+    }
+
+    #[test]
+    fn builds_ir_and_mir_commands() {
+        let options = |emit| AsmOptions {
+            emit,
+            opt_level: 1,
+            ..AsmOptions::default()
+        };
+
+        let (file, command) = Languages::Rust.asm_command(&options(Emit::Ir)).unwrap();
+        assert_eq!(file, "program.rs");
+        assert!(command.contains("-C opt-level=1 --emit=llvm-ir=-"));
+
+        let (_, command) = Languages::Rust.asm_command(&options(Emit::Mir)).unwrap();
+        assert!(command.contains("--emit=mir=-"));
+
+        let (file, command) = Languages::Cpp.asm_command(&options(Emit::Ir)).unwrap();
+        assert_eq!(file, "program.cpp");
+        assert!(command.starts_with("g++ -S -O1 -fdump-tree-optimized=stdout"));
+
+        let (_, command) = Languages::Haskell.asm_command(&options(Emit::Ir)).unwrap();
+        assert!(command.contains("-ddump-simpl"));
+    }
+
+    #[test]
+    fn builds_asm_commands_with_the_syntax() {
+        let options = AsmOptions {
+            syntax: Syntax::Att,
+            ..AsmOptions::default()
+        };
+
+        let (file, command) = Languages::C.asm_command(&options).unwrap();
+        assert_eq!(file, "program.c");
+        assert!(command.starts_with("gcc -S -O2 -masm=att"));
+
+        let (_, command) = Languages::Rust.asm_command(&options).unwrap();
+        assert!(command.contains("-x86-asm-syntax=att"));
+    }
+
+    #[test]
+    fn rejects_unsupported_outputs() {
+        let mir = AsmOptions {
+            emit: Emit::Mir,
+            ..AsmOptions::default()
+        };
+
+        assert!(Languages::C.asm_command(&mir).is_none());
+        assert!(Languages::Golang.asm_command(&mir).is_none());
+        assert!(Languages::Python
+            .asm_command(&AsmOptions::default())
+            .is_none());
+    }
+}
@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+use docker_api::conn::TtyChunk;
+
+use super::{
+    exit_status::ExitStatus,
+    usage::{format_bytes, format_duration},
+};
+
+/// Measurements of a single benchmark run.
+#[derive(Debug, Clone, Copy)]
+pub struct BenchRun {
+    pub wall_time: Duration,
+    pub cpu_time: Duration,
+}
+
+/// The result of [`Hypervisor::bench`](super::Hypervisor::bench).
+#[derive(Debug)]
+pub struct BenchReport {
+    /// The output of the first run, which also built the program
+    pub output: Vec<TtyChunk>,
+    /// How the last run exited, if it got to report it; measuring stops at the first failure
+    pub status: Option<ExitStatus>,
+    /// How many measured runs were asked for
+    pub requested: usize,
+    /// The measured runs, fewer than requested if the time budget ran out
+    pub runs: Vec<BenchRun>,
+    /// The most memory used at once across all runs, in bytes
+    pub peak_memory: u64,
+    /// Whether `peak_memory` includes building the program, which happens in the same container
+    pub includes_build: bool,
+}
+
+/// The spread of a measurement across runs.
+#[derive(Debug, Clone, Copy)]
+pub struct Summary {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Summary {
+    fn of(mut values: Vec<Duration>) -> Option<Self> {
+        values.sort_unstable();
+
+        Some(Self {
+            min: *values.first()?,
+            median: values[values.len() / 2],
+            max: *values.last()?,
+        })
+    }
+}
+
+impl BenchReport {
+    pub fn wall_time(&self) -> Option<Summary> {
+        Summary::of(self.runs.iter().map(|run| run.wall_time).collect())
+    }
+
+    pub fn cpu_time(&self) -> Option<Summary> {
+        Summary::of(self.runs.iter().map(|run| run.cpu_time).collect())
+    }
+}
+
+/// Formats the measurements of a benchmark for Discord.
+pub fn format_report(report: &BenchReport) -> String {
+    match report.status {
+        Some(ExitStatus::CompileFailure) => return "-# 🛠️ Compilation failed".to_string(),
+        Some(status) if !status.success() => {
+            return format!("-# ❌ Benchmark stopped, the program {status}")
+        }
+        _ => {}
+    }
+
+    let (Some(wall), Some(cpu)) = (report.wall_time(), report.cpu_time()) else {
+        return "-# ⏱️ No runs finished within the time budget".to_string();
+    };
+
+    let row = |name: &str, summary: Summary| {
+        format!(
+            "{name:<6}{:>10}{:>10}{:>10}",
+            format_duration(summary.min),
+            format_duration(summary.median),
+            format_duration(summary.max),
+        )
+    };
+    let runs = if report.runs.len() < report.requested {
+        format!(
+            "{} of {} runs (time budget exceeded)",
+            report.runs.len(),
+            report.requested
+        )
+    } else {
+        format!("{} runs", report.runs.len())
+    };
+
+    let build = if report.includes_build {
+        " including the build"
+    } else {
+        ""
+    };
+
+    format!(
+        "```\n{:<6}{:>10}{:>10}{:>10}\n{}\n{}\n```\n-# ⏱️ {runs} • peak memory {}{build}",
+        "",
+        "min",
+        "median",
+        "max",
+        row("wall", wall),
+        row("cpu", cpu),
+        format_bytes(report.peak_memory),
+    )
+}
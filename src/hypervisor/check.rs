@@ -0,0 +1,167 @@
+use std::fmt::Write;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::render::{self, MAX_INLINE_OUTPUT};
+
+/// How many diagnostics are listed before the rest are summarized.
+const MAX_DIAGNOSTICS: usize = 20;
+/// How many characters of a diagnostic's message are shown.
+const MAX_MESSAGE_LENGTH: usize = 200;
+
+/// A single problem reported by a checker.
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub file: String,
+    pub line: u32,
+    pub column: Option<u32>,
+    pub message: String,
+}
+
+/// The result of [`Hypervisor::check`](super::Hypervisor::check).
+#[derive(Debug)]
+pub struct CheckReport {
+    /// Everything the checker printed
+    pub output: String,
+    /// The diagnostics that could be parsed from `output`
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl CheckReport {
+    pub fn new(output: String) -> Self {
+        let diagnostics = parse_diagnostics(&output);
+
+        Self {
+            output,
+            diagnostics,
+        }
+    }
+}
+
+/// Parses `file:line[:column]: message` lines, as printed by gcc, clippy (with
+/// `--error-format=short`), shellcheck (with `--format=gcc`), mypy, ghc and go vet, and
+/// `file(line,column): message` lines, as printed by tsc.
+///
+/// Lines that aren't diagnostics, like the notes following one, are skipped.
+pub fn parse_diagnostics(output: &str) -> Vec<Diagnostic> {
+    static RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^(?<file>[^\s:()]+):(?<line>\d+):(?:(?<column>\d+):)?\s*(?<message>.+)$")
+            .unwrap()
+    });
+    static RE_PARENTHESIZED: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^(?<file>[^\s:()]+)\((?<line>\d+),(?<column>\d+)\):\s*(?<message>.+)$")
+            .unwrap()
+    });
+
+    output
+        .lines()
+        .filter_map(|line| {
+            let captures = RE
+                .captures(line)
+                .or_else(|| RE_PARENTHESIZED.captures(line))?;
+            let file = captures.name("file")?.as_str();
+
+            Some(Diagnostic {
+                // checkers print the path they were given, which may be absolute
+                file: file.rsplit('/').next().unwrap_or(file).to_string(),
+                line: captures.name("line")?.as_str().parse().ok()?,
+                column: captures
+                    .name("column")
+                    .and_then(|column| column.as_str().parse().ok()),
+                message: captures.name("message")?.as_str().trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Formats the diagnostics of a check for Discord, in at most about [`MAX_INLINE_OUTPUT`]
+/// characters.
+///
+/// Falls back to the raw output of the checker if it printed something that isn't a diagnostic.
+pub fn format_report(report: &CheckReport) -> String {
+    if report.diagnostics.is_empty() {
+        let output = report.output.trim();
+        if output.is_empty() {
+            return "-# ✅ No problems found".to_string();
+        }
+
+        let (shown, rest) = render::truncate(output, MAX_INLINE_OUTPUT);
+        if rest == 0 {
+            return format!("```\n{shown}\n```");
+        }
+
+        return format!("```\n{shown}...({rest} more characters)\n```");
+    }
+
+    let mut out = String::new();
+    let mut listed = 0;
+    for diagnostic in report.diagnostics.iter().take(MAX_DIAGNOSTICS) {
+        let location = match diagnostic.column {
+            Some(column) => format!("{}:{}:{column}", diagnostic.file, diagnostic.line),
+            None => format!("{}:{}", diagnostic.file, diagnostic.line),
+        };
+        let (message, cut) = render::truncate(&diagnostic.message, MAX_MESSAGE_LENGTH);
+        let ellipsis = if cut > 0 { "..." } else { "" };
+        let line = format!("`{location}` {message}{ellipsis}\n");
+        if out.chars().count() + line.chars().count() > MAX_INLINE_OUTPUT {
+            break;
+        }
+        out.push_str(&line);
+        listed += 1;
+    }
+    if report.diagnostics.len() > listed {
+        let more = report.diagnostics.len() - listed;
+        let _ = writeln!(out, "...and {more} more");
+    }
+
+    let count = report.diagnostics.len();
+    let _ = write!(
+        out,
+        "-# 🔍 {count} {}",
+        if count == 1 {
+            "diagnostic"
+        } else {
+            "diagnostics"
+        }
+    );
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_gcc_and_tsc_diagnostics() {
+        let diagnostics = parse_diagnostics(
+            "/tmp/code/main.c:3:5: warning: unused variable\nnote: declared here\nmain.ts(2,7): error TS2322: nope",
+        );
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].file, "main.c");
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (3, Some(5)));
+        assert_eq!(diagnostics[1].message, "error TS2322: nope");
+    }
+
+    #[test]
+    fn caps_long_diagnostics() {
+        let output = (1..=20)
+            .map(|line| format!("main.rs:{line}:1: {}", "x".repeat(500)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let formatted = format_report(&CheckReport::new(output));
+
+        assert!(formatted.chars().count() < 2000);
+        assert!(formatted.contains("more"));
+    }
+
+    #[test]
+    fn caps_raw_output() {
+        let formatted = format_report(&CheckReport::new("é".repeat(5000)));
+
+        assert!(formatted.chars().count() < 2000);
+        assert!(formatted.contains("more characters"));
+    }
+}
@@ -5,9 +5,21 @@ pub enum ExecError {
     #[error("code execution timed out")]
     Timeout,
 
+    #[error("memory limit exceeded")]
+    OutOfMemory,
+
+    #[error("code execution was cancelled")]
+    Cancelled,
+
     #[error("no output")]
     Empty,
 
+    #[error("{0} is not supported for this language")]
+    Unsupported(&'static str),
+
+    #[error("the formatter failed:\n```\n{0}\n```")]
+    Format(String),
+
     #[error("an error occurred with docker")]
     Docker(#[from] DockerError),
 
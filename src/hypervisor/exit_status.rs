@@ -0,0 +1,74 @@
+use std::fmt;
+
+/// The exit code `run.sh` uses when the program could not be built.
+///
+/// Like `env` and `timeout`, the wrapper reserves 125 for its own failures.
+pub const COMPILE_FAILURE_EXIT_CODE: i32 = 125;
+
+/// How the program run by `run.sh` exited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    Success,
+    /// The program exited with a non-zero code
+    Code(i32),
+    /// The program was killed by a signal
+    Signal(i32),
+    /// The program could not be built
+    CompileFailure,
+}
+
+impl ExitStatus {
+    /// Interprets the exit code of `run.sh`, which the shell sets to 128 + the signal number
+    /// for programs killed by a signal.
+    pub fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Success,
+            COMPILE_FAILURE_EXIT_CODE => Self::CompileFailure,
+            129..=192 => Self::Signal(code - 128),
+            _ => Self::Code(code),
+        }
+    }
+
+    pub fn success(&self) -> bool {
+        *self == Self::Success
+    }
+}
+
+impl fmt::Display for ExitStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Success => write!(f, "exited successfully"),
+            Self::Code(code) => write!(f, "exited with code {code}"),
+            Self::Signal(signal) => match signal_name(*signal) {
+                Some((name, description)) => write!(f, "killed by {name} ({description})"),
+                None => write!(f, "killed by signal {signal}"),
+            },
+            Self::CompileFailure => write!(f, "compilation failed"),
+        }
+    }
+}
+
+/// The name and a description of the common signals on Linux.
+fn signal_name(signal: i32) -> Option<(&'static str, &'static str)> {
+    let name = match signal {
+        1 => ("SIGHUP", "hangup"),
+        2 => ("SIGINT", "interrupted"),
+        3 => ("SIGQUIT", "quit"),
+        4 => ("SIGILL", "illegal instruction"),
+        5 => ("SIGTRAP", "trace trap"),
+        6 => ("SIGABRT", "aborted"),
+        7 => ("SIGBUS", "bus error"),
+        8 => ("SIGFPE", "floating point exception"),
+        9 => ("SIGKILL", "killed"),
+        11 => ("SIGSEGV", "segmentation fault"),
+        13 => ("SIGPIPE", "broken pipe"),
+        14 => ("SIGALRM", "alarm clock"),
+        15 => ("SIGTERM", "terminated"),
+        24 => ("SIGXCPU", "CPU time limit exceeded"),
+        25 => ("SIGXFSZ", "file size limit exceeded"),
+        31 => ("SIGSYS", "bad system call"),
+        _ => return None,
+    };
+
+    Some(name)
+}
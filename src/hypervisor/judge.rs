@@ -0,0 +1,255 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::render::{self, MAX_INLINE_OUTPUT};
+
+use super::usage::format_duration;
+
+/// How many differing lines of a failed case are shown.
+const MAX_DIFF_LINES: usize = 10;
+/// How many characters of a line are shown in a diff.
+const MAX_LINE_LENGTH: usize = 100;
+/// How many characters of a diff are shown, give or take a line.
+const MAX_DIFF_LENGTH: usize = 500;
+
+/// An input fed to a program through stdin and the output it's expected to print.
+#[derive(Debug, Clone, Copy)]
+pub struct TestCase<'a> {
+    pub input: &'a str,
+    pub expected: &'a str,
+}
+
+/// The verdict of a test case or of a whole submission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Accepted,
+    WrongAnswer,
+    TimeLimitExceeded,
+    CompilationError,
+}
+
+impl Verdict {
+    /// The name of the verdict as stored in the `execution` table.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Verdict::Accepted => "accepted",
+            Verdict::WrongAnswer => "wrong_answer",
+            Verdict::TimeLimitExceeded => "time_limit_exceeded",
+            Verdict::CompilationError => "compilation_error",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Verdict::Accepted => "Accepted",
+            Verdict::WrongAnswer => "Wrong answer",
+            Verdict::TimeLimitExceeded => "Time limit exceeded",
+            Verdict::CompilationError => "Compilation error",
+        }
+    }
+}
+
+/// How a single test case went.
+#[derive(Debug)]
+pub struct CaseResult {
+    pub verdict: Verdict,
+    /// What the program printed to stdout
+    pub output: String,
+    pub wall_time: Duration,
+}
+
+/// The result of [`Hypervisor::judge`](super::Hypervisor::judge).
+#[derive(Debug)]
+pub struct JudgeReport {
+    /// The results of the cases that were run, in order
+    pub cases: Vec<CaseResult>,
+    /// How many cases were given; judging stops at the first time limit exceeded
+    pub total: usize,
+    /// What the build printed if it failed, in which case no case was judged
+    pub compile_output: Option<String>,
+}
+
+impl JudgeReport {
+    /// The verdict of the submission: the verdict of the first failed case, if any.
+    pub fn verdict(&self) -> Verdict {
+        if self.compile_output.is_some() {
+            return Verdict::CompilationError;
+        }
+
+        self.cases
+            .iter()
+            .map(|case| case.verdict)
+            .find(|verdict| *verdict != Verdict::Accepted)
+            .unwrap_or(Verdict::Accepted)
+    }
+
+    pub fn passed(&self) -> usize {
+        self.cases
+            .iter()
+            .filter(|case| case.verdict == Verdict::Accepted)
+            .count()
+    }
+}
+
+/// Whether `output` matches `expected`, ignoring trailing whitespace on every line and trailing
+/// blank lines.
+pub fn output_matches(output: &str, expected: &str) -> bool {
+    normalize(output).eq(normalize(expected))
+}
+
+fn normalize(text: &str) -> impl Iterator<Item = &str> {
+    text.trim_end().lines().map(str::trim_end)
+}
+
+/// Formats the results of judging for Discord, with a diff for every wrong answer as long as
+/// the report stays within about [`MAX_INLINE_OUTPUT`] characters.
+pub fn format_report(report: &JudgeReport, cases: &[TestCase<'_>]) -> String {
+    let mut out = String::new();
+    if let Some(output) = &report.compile_output {
+        let output = output.trim();
+        if !output.is_empty() {
+            let (shown, rest) = render::truncate(output, MAX_INLINE_OUTPUT);
+            let more = if rest > 0 {
+                format!("...({rest} more characters)")
+            } else {
+                String::new()
+            };
+            let _ = writeln!(out, "```\n{shown}{more}\n```");
+        }
+    }
+
+    let mut omitted = 0;
+    for (i, (result, case)) in report.cases.iter().zip(cases).enumerate() {
+        let number = i + 1;
+        let time = format_duration(result.wall_time);
+        match result.verdict {
+            Verdict::Accepted => {
+                let _ = writeln!(out, "✅ Case {number} passed ({time})");
+            }
+            Verdict::WrongAnswer => {
+                let _ = writeln!(out, "❌ Case {number}: wrong answer ({time})");
+                let diff = format!("```diff\n{}```\n", diff(case.expected, &result.output));
+                if out.chars().count() + diff.chars().count() > MAX_INLINE_OUTPUT {
+                    omitted += 1;
+                } else {
+                    out.push_str(&diff);
+                }
+            }
+            Verdict::TimeLimitExceeded => {
+                let _ = writeln!(out, "⏰ Case {number}: time limit exceeded");
+            }
+            Verdict::CompilationError => {
+                let _ = writeln!(out, "🛠️ Case {number}: compilation error");
+            }
+        }
+    }
+    for number in report.cases.len() + 1..=report.total {
+        let _ = writeln!(out, "⏭️ Case {number} skipped");
+    }
+    if omitted > 0 {
+        let _ = writeln!(
+            out,
+            "-# {omitted} {} left out to keep the report short",
+            if omitted == 1 { "diff" } else { "diffs" }
+        );
+    }
+
+    let _ = write!(
+        out,
+        "-# ⚖️ {} • {}/{} cases passed",
+        report.verdict().label(),
+        report.passed(),
+        report.total
+    );
+
+    out
+}
+
+/// Lists the lines that differ between `expected` and `output`, `-` for expected and `+` for
+/// actual ones.
+fn diff(expected: &str, output: &str) -> String {
+    let expected = normalize(expected).collect::<Vec<_>>();
+    let output = normalize(output).collect::<Vec<_>>();
+
+    let mut out = String::new();
+    let mut shown = 0;
+    for i in 0..expected.len().max(output.len()) {
+        let (want, got) = (expected.get(i), output.get(i));
+        if want == got {
+            continue;
+        }
+        if shown == MAX_DIFF_LINES || out.chars().count() >= MAX_DIFF_LENGTH {
+            out.push_str("...\n");
+            break;
+        }
+        shown += 1;
+
+        if let Some(want) = want {
+            let _ = writeln!(out, "- {}", truncate_line(want));
+        }
+        if let Some(got) = got {
+            let _ = writeln!(out, "+ {}", truncate_line(got));
+        }
+    }
+
+    out
+}
+
+fn truncate_line(line: &str) -> String {
+    match line.char_indices().nth(MAX_LINE_LENGTH) {
+        Some((end, _)) => format!("{}...", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wrong_answer(output: String) -> CaseResult {
+        CaseResult {
+            verdict: Verdict::WrongAnswer,
+            output,
+            wall_time: Duration::from_millis(5),
+        }
+    }
+
+    #[test]
+    fn matches_ignoring_trailing_whitespace() {
+        assert!(output_matches("1 \n2\n\n", "1\n2"));
+        assert!(!output_matches("1\n3", "1\n2"));
+    }
+
+    #[test]
+    fn caps_long_reports() {
+        let expected = "y".repeat(1000);
+        let cases = vec![
+            TestCase {
+                input: "",
+                expected: &expected,
+            };
+            20
+        ];
+        let report = JudgeReport {
+            cases: (0..20).map(|_| wrong_answer("x\n".repeat(50))).collect(),
+            total: 20,
+            compile_output: None,
+        };
+        let formatted = format_report(&report, &cases);
+
+        assert!(formatted.chars().count() < 2000);
+        assert!(formatted.contains("left out"));
+    }
+
+    #[test]
+    fn reports_compilation_errors() {
+        let report = JudgeReport {
+            cases: vec![],
+            total: 3,
+            compile_output: Some("main.c:1:1: error: nope".to_string()),
+        };
+
+        assert_eq!(report.verdict(), Verdict::CompilationError);
+        assert!(format_report(&report, &[]).contains("error: nope"));
+    }
+}
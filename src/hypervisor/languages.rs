@@ -2,6 +2,17 @@ use serde::{Deserialize, Serialize};
 
 use crate::config::CONFIG;
 
+use super::asm::{AsmOptions, Emit};
+
+/// The static checker of a language, see [`Languages::checker`].
+#[derive(Debug, Clone, Copy)]
+pub struct Checker {
+    /// The file the code is written to
+    pub file: &'static str,
+    /// The command that checks `file` without running it
+    pub command: &'static str,
+}
+
 macro_rules! define_languages {
     ($(($variant:ident, $name:expr, [$($alias:expr),*])),* $(,)?) => {
         #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -31,6 +42,13 @@ macro_rules! define_languages {
                 }
             }
 
+            /// The names a code block may use for this language.
+            pub fn aliases(&self) -> &'static [&'static str] {
+                match self {
+                    $(Languages::$variant => &[$($alias),*],)*
+                }
+            }
+
             pub fn enabled(&self) -> bool {
                 CONFIG.languages.contains(self)
             }
@@ -41,6 +59,247 @@ macro_rules! define_languages {
                     .cloned()
                     .collect()
             }
+
+            /// The command that runs the program `run.sh` left in the working directory again,
+            /// without building it a second time.
+            pub fn run_command(&self) -> Option<&'static str> {
+                let command = match self {
+                    Languages::Apl => "apl --OFF -s -f program.apl",
+                    Languages::Bash => "bash program.sh",
+                    Languages::BrainFuck => r#"bf "$(cat program.bf)""#,
+                    Languages::C
+                    | Languages::Cpp
+                    | Languages::Golang
+                    | Languages::OCaml
+                    | Languages::Pascal
+                    | Languages::Rust => "./program",
+                    Languages::Clojure => "clojure program.clj",
+                    Languages::CSharp | Languages::FSharp => "mono program.exe",
+                    Languages::Elixir => "elixir program.exs",
+                    Languages::Haskell => "ghc -e main program.hs",
+                    Languages::Java => "java Main",
+                    Languages::JavaScript => r#"node -p "$(cat program.js)""#,
+                    Languages::Julia => "julia program.jl",
+                    Languages::Lua => "lua5.3 program.lua",
+                    Languages::Perl => "perl program.pl",
+                    Languages::Php => "php program.php",
+                    Languages::Prolog => "swipl --quiet program.pl",
+                    Languages::Python => "python program.py",
+                    Languages::Racket => "racket program.rkt",
+                    Languages::Ruby => "ruby program.rb",
+                    Languages::SQL => "sqlite3 :memory: < program.sql",
+                    Languages::TypeScript => r#"tsx -p "$(cat program.ts)""#,
+                    Languages::Crystal | Languages::Idris => return None,
+                };
+
+                Some(command)
+            }
+
+            /// Whether `run.sh` builds the program before running it, in the same container.
+            pub fn builds(&self) -> bool {
+                matches!(
+                    self,
+                    Languages::C
+                        | Languages::Cpp
+                        | Languages::Crystal
+                        | Languages::CSharp
+                        | Languages::FSharp
+                        | Languages::Golang
+                        | Languages::Idris
+                        | Languages::Java
+                        | Languages::OCaml
+                        | Languages::Pascal
+                        | Languages::Rust
+                )
+            }
+
+            /// The extension of a source file in the language.
+            pub fn extension(&self) -> &'static str {
+                match self {
+                    Languages::Apl => "apl",
+                    Languages::Bash => "sh",
+                    Languages::BrainFuck => "bf",
+                    Languages::C => "c",
+                    Languages::Clojure => "clj",
+                    Languages::Cpp => "cpp",
+                    Languages::Crystal => "cr",
+                    Languages::CSharp => "cs",
+                    Languages::Elixir => "exs",
+                    Languages::FSharp => "fs",
+                    Languages::Golang => "go",
+                    Languages::Haskell => "hs",
+                    Languages::Idris => "idr",
+                    Languages::Java => "java",
+                    Languages::JavaScript => "js",
+                    Languages::Julia => "jl",
+                    Languages::Lua => "lua",
+                    Languages::OCaml => "ml",
+                    Languages::Pascal => "pas",
+                    Languages::Perl | Languages::Prolog => "pl",
+                    Languages::Php => "php",
+                    Languages::Python => "py",
+                    Languages::Racket => "rkt",
+                    Languages::Ruby => "rb",
+                    Languages::Rust => "rs",
+                    Languages::SQL => "sql",
+                    Languages::TypeScript => "ts",
+                }
+            }
+
+            /// The command that reads code from stdin and writes it formatted to stdout, if the image
+            /// of the language ships a formatter.
+            pub fn format_command(&self) -> Option<&'static str> {
+                let command = match self {
+                    Languages::Bash => "shfmt",
+                    Languages::C => "clang-format --assume-filename=program.c",
+                    Languages::Cpp => "clang-format --assume-filename=program.cpp",
+                    Languages::Elixir => "mix format -",
+                    Languages::Golang => "gofmt",
+                    Languages::Haskell => "ormolu",
+                    Languages::JavaScript => "prettier --stdin-filepath program.js",
+                    Languages::Python => "black --quiet -",
+                    Languages::Rust => "rustfmt --edition 2021",
+                    Languages::TypeScript => "prettier --stdin-filepath program.ts",
+                    Languages::Apl
+                    | Languages::BrainFuck
+                    | Languages::Clojure
+                    | Languages::Crystal
+                    | Languages::CSharp
+                    | Languages::FSharp
+                    | Languages::Idris
+                    | Languages::Java
+                    | Languages::Julia
+                    | Languages::Lua
+                    | Languages::OCaml
+                    | Languages::Pascal
+                    | Languages::Perl
+                    | Languages::Php
+                    | Languages::Prolog
+                    | Languages::Racket
+                    | Languages::Ruby
+                    | Languages::SQL => return None,
+                };
+
+                Some(command)
+            }
+
+            /// The static checker of the language, if its image ships one.
+            pub fn checker(&self) -> Option<Checker> {
+                let (file, command) = match self {
+                    Languages::Bash => ("program.sh", "shellcheck --format=gcc program.sh"),
+                    Languages::C => ("program.c", "gcc -fsyntax-only -Wall -Wextra program.c"),
+                    Languages::Cpp => ("program.cpp", "g++ -fsyntax-only -Wall -Wextra program.cpp"),
+                    Languages::Golang => ("program.go", "GOCACHE=\"$PWD/cache\" go vet program.go"),
+                    Languages::Haskell => ("program.hs", "ghc -fno-code -Wall program.hs"),
+                    Languages::JavaScript => ("program.js", "node --check program.js"),
+                    Languages::Python => (
+                        "program.py",
+                        "mypy --no-error-summary --no-color-output program.py",
+                    ),
+                    Languages::Rust => (
+                        "program.rs",
+                        "clippy-driver --edition 2021 --error-format=short --emit=metadata -W clippy::all program.rs",
+                    ),
+                    Languages::TypeScript => ("program.ts", "tsc --noEmit --strict --pretty false program.ts"),
+                    Languages::Apl
+                    | Languages::BrainFuck
+                    | Languages::Clojure
+                    | Languages::Crystal
+                    | Languages::CSharp
+                    | Languages::Elixir
+                    | Languages::FSharp
+                    | Languages::Idris
+                    | Languages::Java
+                    | Languages::Julia
+                    | Languages::Lua
+                    | Languages::OCaml
+                    | Languages::Pascal
+                    | Languages::Perl
+                    | Languages::Php
+                    | Languages::Prolog
+                    | Languages::Racket
+                    | Languages::Ruby
+                    | Languages::SQL => return None,
+                };
+
+                Some(Checker { file, command })
+            }
+
+            /// The file code is written to and the command that prints what it compiles to, if the
+            /// language compiles ahead of time and supports `options`.
+            pub fn asm_command(&self, options: &AsmOptions) -> Option<(&'static str, String)> {
+                let AsmOptions {
+                    emit,
+                    opt_level,
+                    syntax,
+                } = options;
+                let syntax = syntax.as_str();
+
+                let (file, command) = match (self, emit) {
+                    (Languages::C | Languages::Cpp, Emit::Asm) => {
+                        let (compiler, file) = self.gcc();
+                        let command = format!(
+                            "{compiler} -S -O{opt_level} -masm={syntax} -fno-asynchronous-unwind-tables -o - {file} | c++filt"
+                        );
+
+                        (file, command)
+                    }
+                    (Languages::C | Languages::Cpp, Emit::Ir) => {
+                        let (compiler, file) = self.gcc();
+                        let command = format!(
+                            "{compiler} -S -O{opt_level} -fdump-tree-optimized=stdout -o /dev/null {file} | c++filt"
+                        );
+
+                        (file, command)
+                    }
+                    (Languages::Rust, Emit::Asm) => (
+                        "program.rs",
+                        format!("rustc --edition 2021 -C opt-level={opt_level} -C llvm-args=-x86-asm-syntax={syntax} --emit=asm=- program.rs | c++filt"),
+                    ),
+                    (Languages::Rust, Emit::Ir) => (
+                        "program.rs",
+                        format!("rustc --edition 2021 -C opt-level={opt_level} --emit=llvm-ir=- program.rs | c++filt"),
+                    ),
+                    (Languages::Rust, Emit::Mir) => (
+                        "program.rs",
+                        format!("rustc --edition 2021 -C opt-level={opt_level} --emit=mir=- program.rs"),
+                    ),
+                    (Languages::Golang, Emit::Asm) => {
+                        // go only knows optimized and unoptimized builds, and prints plan 9 assembly
+                        let flags = if *opt_level == 0 { "-S -N -l" } else { "-S" };
+                        let command = format!(
+                            "GOCACHE=\"$PWD/cache\" go build -gcflags='{flags}' -o /dev/null program.go 2>&1"
+                        );
+
+                        ("program.go", command)
+                    }
+                    (Languages::Haskell, Emit::Asm | Emit::Ir) => {
+                        let dump = if *emit == Emit::Asm {
+                            "-ddump-asm"
+                        } else {
+                            "-ddump-simpl -dsuppress-all"
+                        };
+                        // ghc doesn't go beyond -O2
+                        let command = format!(
+                            "ghc -O{} {dump} -fforce-recomp -no-link program.hs",
+                            (*opt_level).min(2)
+                        );
+
+                        ("program.hs", command)
+                    }
+                    _ => return None,
+                };
+
+                Some((file, command))
+            }
+
+            fn gcc(&self) -> (&'static str, &'static str) {
+                if *self == Languages::Cpp {
+                    ("g++", "program.cpp")
+                } else {
+                    ("gcc", "program.c")
+                }
+            }
         }
 
         pub const LANGUAGES: &[Languages] = &[
@@ -0,0 +1,123 @@
+use std::time::Duration;
+
+/// Prints the cgroup counters of the container it runs in as `key value` lines.
+///
+/// Handles both cgroup v2 and v1 hierarchies.
+pub(crate) const SAMPLE_SCRIPT: &str = r#"
+if [ -f /sys/fs/cgroup/cpu.stat ]; then
+    echo "cpu_usec $(sed -n 's/^usage_usec //p' /sys/fs/cgroup/cpu.stat)"
+    echo "memory_peak $(cat /sys/fs/cgroup/memory.peak 2>/dev/null)"
+    echo "oom_kills $(sed -n 's/^oom_kill //p' /sys/fs/cgroup/memory.events)"
+else
+    echo "cpu_usec $(( $(cat /sys/fs/cgroup/cpuacct/cpuacct.usage) / 1000 ))"
+    echo "memory_peak $(cat /sys/fs/cgroup/memory/memory.max_usage_in_bytes)"
+    echo "oom_kills $(sed -n 's/^oom_kill //p' /sys/fs/cgroup/memory/memory.oom_control)"
+fi
+"#;
+
+/// A snapshot of the resource counters of a container.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CgroupSample {
+    /// CPU time used by everything that ran in the container so far
+    pub cpu_time: Duration,
+    /// The most memory the container has used at once, in bytes
+    pub peak_memory: u64,
+    /// How many processes in the container were killed for exceeding the memory limit
+    pub oom_kills: u64,
+}
+
+impl CgroupSample {
+    /// Parses the output of [`SAMPLE_SCRIPT`], ignoring counters that aren't available.
+    pub fn parse(output: &str) -> Self {
+        let mut sample = Self::default();
+        for line in output.lines() {
+            let Some((key, value)) = line.split_once(' ') else {
+                continue;
+            };
+            let Ok(value) = value.trim().parse::<u64>() else {
+                continue;
+            };
+
+            match key {
+                "cpu_usec" => sample.cpu_time = Duration::from_micros(value),
+                "memory_peak" => sample.peak_memory = value,
+                "oom_kills" => sample.oom_kills = value,
+                _ => {}
+            }
+        }
+
+        sample
+    }
+}
+
+/// The resources used by a single execution.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ResourceUsage {
+    pub cpu_time: Duration,
+    /// The most memory used at once, in bytes
+    pub peak_memory: u64,
+    /// Whether the program was killed for exceeding the memory limit
+    pub oom_killed: bool,
+    /// Whether the figures include building the program, which the container's counters can't
+    /// tell apart from running it
+    pub includes_build: bool,
+}
+
+impl ResourceUsage {
+    /// The usage between two samples of the same container.
+    pub fn between(before: CgroupSample, after: CgroupSample) -> Self {
+        Self {
+            cpu_time: after.cpu_time.saturating_sub(before.cpu_time),
+            peak_memory: after.peak_memory,
+            oom_killed: after.oom_kills > before.oom_kills,
+            includes_build: false,
+        }
+    }
+}
+
+impl std::fmt::Display for ResourceUsage {
+    /// Formats the usage like `0.42s • 23.1 MiB`, followed by `including the build` if it does.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.2}s • {}",
+            self.cpu_time.as_secs_f64(),
+            format_bytes(self.peak_memory)
+        )?;
+        if self.includes_build {
+            write!(f, " including the build")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Formats a byte count like `23.1 MiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Formats a duration like `850µs`, `12.3ms` or `1.25s`.
+pub fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_micros();
+    if micros < 1000 {
+        format!("{micros}µs")
+    } else if micros < 1_000_000 {
+        format!("{:.1}ms", micros as f64 / 1000.0)
+    } else {
+        format!("{:.2}s", duration.as_secs_f64())
+    }
+}
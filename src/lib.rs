@@ -3,11 +3,21 @@ use std::sync::Arc;
 use state::BotState;
 use vesper::prelude::Framework;
 
+pub mod autocomplete;
+pub mod blocklist;
 pub mod commands;
+pub mod components;
 pub mod config;
 pub mod events;
+pub mod execution;
 pub mod hypervisor;
+pub mod metrics;
+pub mod modals;
 pub mod parsers;
+pub mod rate_limit;
+pub mod render;
+pub mod reply;
+pub mod settings;
 pub mod state;
 
 pub type BotFramework = Arc<Framework<BotState>>;
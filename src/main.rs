@@ -5,10 +5,13 @@ use std::time::Duration;
 use anyhow::Result;
 use dotenvy::dotenv;
 use run_sh::config::CONFIG;
-use run_sh::events::{interaction_create, message_create, message_update, thread_create};
+use run_sh::events::{
+    interaction_create, message_create, message_delete, message_delete_bulk, message_update,
+    thread_create,
+};
 use run_sh::hypervisor::Hypervisor;
 use run_sh::state::BotState;
-use run_sh::{commands, BotFramework};
+use run_sh::{blocklist::Blocklist, commands, metrics, BotFramework};
 use sqlx::postgres::PgPoolOptions;
 use tokio::task::JoinSet;
 use tracing_subscriber::{fmt, prelude::*, EnvFilter, Registry};
@@ -36,6 +39,12 @@ async fn main() -> Result<()> {
         .init();
     tracing::info!(env = CONFIG.environment.to_string(), "starting up");
 
+    #[cfg(feature = "metrics")]
+    if let Some(address) = CONFIG.metrics_address {
+        metrics::install(address)?;
+        tracing::info!("serving metrics on {address}");
+    }
+
     let discord_client = Arc::new(Client::new(CONFIG.discord_token.clone()));
     let hypervisor = Arc::new(Hypervisor::new(CONFIG.docker_endpoint.clone()));
 
@@ -58,18 +67,39 @@ async fn main() -> Result<()> {
 
     tracing::info!("initialized database with {count} executions");
 
-    let state = BotState { hypervisor, db };
+    let blocklist = Blocklist::load(&db).await?;
+
+    let state = BotState {
+        hypervisor,
+        db,
+        running: Default::default(),
+        pages: Default::default(),
+        settings: Default::default(),
+        blocklist: Arc::new(blocklist),
+        rate_limits: Default::default(),
+    };
 
     tracing::info!("initializing docker containers");
     state.hypervisor.init().await?;
 
     let framework = Arc::new(
         Framework::builder(discord_client.clone(), CONFIG.discord_application_id, state)
+            .command(commands::check_code::check_code)
             .command(commands::execute_code::execute_code)
+            .command(commands::block::block)
+            .command(commands::block::unblock)
+            .command(commands::format_code::format_code)
+            .command(commands::history::history)
             .command(commands::languages::languages)
+            .command(commands::run::run)
+            .command(commands::settings::settings)
+            .command(commands::stats::stats)
             .build(),
     );
 
+    tracing::info!("registering commands");
+    commands::register(&framework).await?;
+
     let config = Config::new(
         CONFIG.discord_token.clone(),
         Intents::GUILDS | Intents::GUILD_MESSAGES | Intents::MESSAGE_CONTENT,
@@ -118,6 +148,8 @@ async fn runner(mut shard: Shard, framework: BotFramework) {
             }
         };
 
+        metrics::record_gateway_event(shard_id, event.kind());
+
         tokio::spawn({
             let framework = framework.clone();
             async move {
@@ -146,6 +178,22 @@ async fn runner(mut shard: Shard, framework: BotFramework) {
                             }
                         }
                     }
+                    Event::MessageDelete(event) => {
+                        match message_delete::handle(framework.clone(), event).await {
+                            Ok(_) => {}
+                            Err(e) => {
+                                tracing::error!("failed to handle message delete event - {e:#?}")
+                            }
+                        }
+                    }
+                    Event::MessageDeleteBulk(event) => {
+                        match message_delete_bulk::handle(framework.clone(), event).await {
+                            Ok(_) => {}
+                            Err(e) => tracing::error!(
+                                "failed to handle message delete bulk event - {e:#?}"
+                            ),
+                        }
+                    }
                     Event::MessageUpdate(event) => {
                         match message_update::handle(framework.clone(), event).await {
                             Ok(_) => {}
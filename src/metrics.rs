@@ -0,0 +1,188 @@
+//! Prometheus metrics.
+//!
+//! The recorders in this module are always available so call sites don't have to be
+//! feature-gated; without the `metrics` feature they compile down to nothing.
+#![cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+
+use std::time::Duration;
+
+use twilight_model::gateway::event::EventType;
+
+use crate::hypervisor::{exec_error::ExecError, languages::Languages};
+
+/// Buckets (in seconds) used for every `*_seconds` histogram.
+#[cfg(feature = "metrics")]
+const SECONDS_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0,
+];
+
+/// Installs the global recorder and starts the HTTP listener serving `/metrics`.
+#[cfg(feature = "metrics")]
+pub fn install(address: std::net::SocketAddr) -> anyhow::Result<()> {
+    use metrics_exporter_prometheus::{Matcher, PrometheusBuilder};
+
+    PrometheusBuilder::new()
+        .with_http_listener(address)
+        .set_buckets_for_metric(Matcher::Suffix("seconds".to_string()), SECONDS_BUCKETS)?
+        .install()?;
+
+    ::metrics::describe_counter!(
+        "runsh_executions_total",
+        "Code executions by language and outcome"
+    );
+    ::metrics::describe_gauge!(
+        "runsh_executions_queued",
+        "Executions waiting for a container slot"
+    );
+    ::metrics::describe_gauge!(
+        "runsh_executions_running",
+        "Executions holding a container slot"
+    );
+    ::metrics::describe_histogram!(
+        "runsh_hypervisor_step_duration_seconds",
+        ::metrics::Unit::Seconds,
+        "Time spent in each hypervisor step of an execution"
+    );
+    ::metrics::describe_histogram!(
+        "runsh_image_build_duration_seconds",
+        ::metrics::Unit::Seconds,
+        "Time spent building language images"
+    );
+    ::metrics::describe_counter!(
+        "runsh_gateway_events_total",
+        "Gateway events received by shard and event type"
+    );
+    ::metrics::describe_counter!(
+        "runsh_discord_http_errors_total",
+        "Failed requests to the Discord HTTP API by status code"
+    );
+
+    Ok(())
+}
+
+/// How an execution ended, used as the `outcome` label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The program ran to completion
+    Ok,
+    /// The program was killed after exceeding the time limit
+    Timeout,
+    /// The program was killed after exceeding the memory limit
+    OutOfMemory,
+    /// The execution was cancelled before the program finished
+    Cancelled,
+    /// The program could not be compiled
+    CompileFailure,
+    /// Something went wrong on our side
+    Error,
+}
+
+impl Outcome {
+    pub fn of<T>(result: &Result<T, ExecError>) -> Self {
+        match result {
+            Ok(_) => Self::Ok,
+            Err(e) => Self::of_error(e),
+        }
+    }
+
+    pub fn of_error(error: &ExecError) -> Self {
+        match error {
+            ExecError::Timeout => Self::Timeout,
+            ExecError::OutOfMemory => Self::OutOfMemory,
+            ExecError::Cancelled => Self::Cancelled,
+            _ => Self::Error,
+        }
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Timeout => "timeout",
+            Self::OutOfMemory => "out_of_memory",
+            Self::Cancelled => "cancelled",
+            Self::CompileFailure => "compile_failure",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// Records a finished execution.
+pub fn record_execution(language: &Languages, outcome: Outcome) {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(
+        "runsh_executions_total",
+        "language" => language.to_string(),
+        "outcome" => outcome.as_str(),
+    )
+    .increment(1);
+}
+
+/// Counts an execution under a gauge for as long as it is alive.
+#[must_use]
+pub struct ExecutionGauge(#[cfg_attr(not(feature = "metrics"), allow(dead_code))] &'static str);
+
+impl ExecutionGauge {
+    /// Counts an execution as waiting for a container slot.
+    pub fn queued() -> Self {
+        Self::new("runsh_executions_queued")
+    }
+
+    /// Counts an execution as holding a container slot.
+    pub fn running() -> Self {
+        Self::new("runsh_executions_running")
+    }
+
+    fn new(gauge: &'static str) -> Self {
+        #[cfg(feature = "metrics")]
+        ::metrics::gauge!(gauge).increment(1.0);
+
+        Self(gauge)
+    }
+}
+
+impl Drop for ExecutionGauge {
+    fn drop(&mut self) {
+        #[cfg(feature = "metrics")]
+        ::metrics::gauge!(self.0).decrement(1.0);
+    }
+}
+
+/// Records how long a hypervisor step (`create`, `start`, `exec`, ...) took.
+pub fn record_step(step: &'static str, elapsed: Duration) {
+    #[cfg(feature = "metrics")]
+    ::metrics::histogram!("runsh_hypervisor_step_duration_seconds", "step" => step).record(elapsed);
+}
+
+/// Records how long building the image for `language` took.
+pub fn record_image_build(language: &Languages, elapsed: Duration) {
+    #[cfg(feature = "metrics")]
+    ::metrics::histogram!(
+        "runsh_image_build_duration_seconds",
+        "language" => language.to_string(),
+    )
+    .record(elapsed);
+}
+
+/// Records a gateway event received by `shard`.
+pub fn record_gateway_event(shard: u32, kind: EventType) {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(
+        "runsh_gateway_events_total",
+        "shard" => shard.to_string(),
+        "event" => format!("{kind:?}"),
+    )
+    .increment(1);
+}
+
+/// Records a failed request to the Discord HTTP API.
+pub fn record_http_error(error: &twilight_http::Error) {
+    #[cfg(feature = "metrics")]
+    {
+        let status = match error.kind() {
+            twilight_http::error::ErrorType::Response { status, .. } => status.get().to_string(),
+            _ => "none".to_string(),
+        };
+
+        ::metrics::counter!("runsh_discord_http_errors_total", "status" => status).increment(1);
+    }
+}
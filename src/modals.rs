@@ -0,0 +1,38 @@
+use twilight_model::application::interaction::{modal::ModalInteractionData, Interaction};
+
+use crate::BotFramework;
+
+pub mod run;
+
+/// Routes a modal submit interaction to its handler.
+///
+/// Custom ids are formatted as `<modal>:<arguments>`, like those of components.
+pub async fn handle(
+    framework: BotFramework,
+    interaction: &Interaction,
+    data: &ModalInteractionData,
+) -> anyhow::Result<()> {
+    let (modal, args) = data
+        .custom_id
+        .split_once(':')
+        .unwrap_or((&data.custom_id, ""));
+
+    match modal {
+        run::ID => run::handle(framework, interaction, args, data).await,
+        _ => {
+            tracing::warn!("received unknown modal {}", data.custom_id);
+
+            Ok(())
+        }
+    }
+}
+
+/// The value of the text input `custom_id`, if it's filled in.
+pub fn value<'a>(data: &'a ModalInteractionData, custom_id: &str) -> Option<&'a str> {
+    data.components
+        .iter()
+        .flat_map(|row| &row.components)
+        .find(|component| component.custom_id == custom_id)
+        .and_then(|component| component.value.as_deref())
+        .filter(|value| !value.is_empty())
+}
@@ -0,0 +1,137 @@
+use std::time::Instant;
+
+use twilight_model::{
+    application::interaction::{modal::ModalInteractionData, Interaction},
+    http::interaction::{InteractionResponse, InteractionResponseType},
+};
+
+use crate::{
+    components::{actions_row, ephemeral_response, respond, stop},
+    config::CONFIG,
+    execution::{self, Finish},
+    parsers::MatchedCode,
+    reply::{execute, Reply},
+    BotFramework,
+};
+
+use super::value;
+
+pub const ID: &str = "run";
+/// The text input holding the code
+pub const CODE: &str = "code";
+/// The text input holding what's fed to the program through stdin
+pub const STDIN: &str = "stdin";
+
+/// Runs the code submitted through the modal of the `/run` command, `args` being its language.
+pub async fn handle(
+    framework: BotFramework,
+    interaction: &Interaction,
+    args: &str,
+    data: &ModalInteractionData,
+) -> anyhow::Result<()> {
+    let Some(source) = value(data, CODE) else {
+        return ephemeral_response(&framework, interaction, "No code given.".to_string()).await;
+    };
+    let stdin = value(data, STDIN);
+    let code = MatchedCode {
+        language: args,
+        options: "",
+        code: source,
+        stdin,
+        cases: vec![],
+    };
+    let settings = framework
+        .data
+        .settings
+        .get(&framework.data.db, interaction.guild_id)
+        .await?;
+    let (language, options) = match execution::prepare(
+        &framework.data,
+        &code,
+        &settings,
+        interaction.author_id(),
+        interaction.guild_id,
+    )
+    .await?
+    {
+        Ok(prepared) => prepared,
+        Err(rejection) => {
+            return ephemeral_response(&framework, interaction, rejection.to_string()).await
+        }
+    };
+
+    respond(
+        &framework,
+        interaction,
+        &InteractionResponse {
+            kind: InteractionResponseType::DeferredChannelMessageWithSource,
+            data: None,
+        },
+    )
+    .await?;
+
+    // there is no message behind the code, so the interaction stands in for it
+    let channel_id = interaction
+        .channel
+        .as_ref()
+        .map(|channel| channel.id.to_string());
+    let message_id = interaction.id.to_string();
+    let language_name = language.to_string();
+    let author_id = interaction.author_id().map(|user| user.to_string());
+    let guild_id = interaction.guild_id.map(|guild| guild.to_string());
+    let execution = sqlx::query!(
+        r#"insert into execution (channel_id, message_id, language, reply_id, author_id, source, stdin, guild_id) values ($1, $2, $3, 'interaction', $4, $5, $6, $7) returning *;"#,
+        channel_id,
+        message_id,
+        language_name,
+        author_id,
+        source,
+        stdin,
+        guild_id
+    )
+    .fetch_one(&framework.data.db)
+    .await?;
+
+    let running = framework.data.running.start(interaction.id.cast());
+    let components = interaction
+        .author_id()
+        .map(|user| vec![stop::row(running.key(), user)])
+        .unwrap_or_default();
+    let client = framework
+        .http_client()
+        .interaction(CONFIG.discord_application_id);
+    let reply = Reply::Interaction {
+        client: &client,
+        token: &interaction.token,
+    };
+    if let Err(e) = reply.edit("-# ⏳ Running your code...", &components).await {
+        tracing::error!("failed to reply to interaction - {e}");
+    }
+
+    let started = Instant::now();
+    let result = execute(
+        &reply,
+        &framework.data.hypervisor,
+        &language,
+        &code,
+        options,
+        running.token(),
+        &components,
+    )
+    .await;
+    execution::finish(
+        &framework.data,
+        &reply,
+        &running,
+        result,
+        started.elapsed(),
+        Finish {
+            id: execution.id,
+            actions: actions_row(execution.id),
+            edit_hint: false,
+        },
+    )
+    .await?;
+
+    Ok(())
+}
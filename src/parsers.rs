@@ -1,26 +1,256 @@
+use std::borrow::Cow;
+
 use once_cell::sync::Lazy;
 use regex::Regex;
 
+use crate::{
+    config::CONFIG,
+    hypervisor::{
+        asm::{AsmOptions, Emit, Syntax},
+        judge::TestCase,
+    },
+};
+
+/// What starts a message whose code is run, unless a guild set another prefix.
+pub const DEFAULT_PREFIX: &str = "$>";
+
+/// How many runs a benchmark does if the amount isn't given.
+const DEFAULT_BENCH_RUNS: usize = 10;
+
 #[derive(Debug)]
 pub struct MatchedCode<'a> {
     pub language: &'a str,
+    /// Options following the language on the opening fence, e.g. `bench=10`
+    pub options: &'a str,
     pub code: &'a str,
+    /// The first ` ```in ` block of the message, fed to the program through stdin
+    pub stdin: Option<&'a str>,
+    /// Test cases given as ` ```in ` blocks, each followed by an ` ```out ` block
+    pub cases: Vec<TestCase<'a>>,
+}
+
+/// What to do with matched code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Run the program once
+    Run,
+    /// Build the program once, then measure this many runs of it
+    Bench(usize),
+    /// Run the program against every test case and compare the outputs
+    Judge,
+    /// Only run the static checker of the language
+    Check,
+    /// Compile the program and show what the compiler emitted
+    Asm(AsmOptions),
+}
+
+/// The options of a [`MatchedCode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    pub mode: Mode,
+    /// Whether compilers should print colored diagnostics
+    pub color: bool,
+    /// Whether long output is attached as separate stdout and stderr files
+    pub split: bool,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum OptionError {
+    #[error("unknown option `{0}`")]
+    Unknown(String),
+
+    #[error("invalid value `{value}` for option `{option}`")]
+    InvalidValue { option: String, value: String },
+
+    #[error("`judge` needs test cases: ```in blocks followed by ```out blocks")]
+    NoTestCases,
+
+    #[error("at most {0} test cases are allowed")]
+    TooManyTestCases(usize),
+
+    #[error("`{0}` only applies to `asm`, `ir` and `mir`")]
+    AsmOnly(String),
+}
+
+impl Options {
+    /// Parses the options of a [`MatchedCode`].
+    pub fn parse(code: &MatchedCode<'_>) -> Result<Self, OptionError> {
+        let mut mode = Mode::Run;
+        let mut color = false;
+        let mut split = false;
+        let mut emit = None;
+        let mut asm = AsmOptions::default();
+        let mut asm_only = None;
+        for option in code.options.split_whitespace() {
+            let (key, value) = match option.split_once('=') {
+                Some((key, value)) => (key, Some(value)),
+                None => (option, None),
+            };
+
+            match key {
+                "bench" => {
+                    let runs = match value {
+                        Some(value) => value
+                            .parse::<usize>()
+                            .ok()
+                            .filter(|&runs| runs > 0)
+                            .ok_or_else(|| OptionError::InvalidValue {
+                                option: key.to_string(),
+                                value: value.to_string(),
+                            })?,
+                        None => DEFAULT_BENCH_RUNS,
+                    };
+
+                    mode = Mode::Bench(runs.min(CONFIG.bench_max_runs));
+                }
+                "asm" | "ir" | "mir" | "check" | "color" | "judge" | "split" if value.is_some() => {
+                    return Err(OptionError::InvalidValue {
+                        option: key.to_string(),
+                        value: value.unwrap_or_default().to_string(),
+                    });
+                }
+                "check" => mode = Mode::Check,
+                "color" => color = true,
+                "split" => split = true,
+                "asm" => emit = Some(Emit::Asm),
+                "ir" => emit = Some(Emit::Ir),
+                "mir" => emit = Some(Emit::Mir),
+                "opt" => {
+                    asm.opt_level = value
+                        .and_then(|value| value.parse().ok())
+                        .filter(|level| *level <= 3)
+                        .ok_or_else(|| OptionError::InvalidValue {
+                            option: key.to_string(),
+                            value: value.unwrap_or_default().to_string(),
+                        })?;
+                    asm_only = Some(key);
+                }
+                "syntax" => {
+                    asm.syntax = match value {
+                        Some("intel") => Syntax::Intel,
+                        Some("att") => Syntax::Att,
+                        _ => {
+                            return Err(OptionError::InvalidValue {
+                                option: key.to_string(),
+                                value: value.unwrap_or_default().to_string(),
+                            })
+                        }
+                    };
+                    asm_only = Some(key);
+                }
+                "judge" => {
+                    if code.cases.is_empty() {
+                        return Err(OptionError::NoTestCases);
+                    }
+                    if code.cases.len() > CONFIG.judge_max_cases {
+                        return Err(OptionError::TooManyTestCases(CONFIG.judge_max_cases));
+                    }
+
+                    mode = Mode::Judge;
+                }
+                _ => return Err(OptionError::Unknown(key.to_string())),
+            }
+        }
+
+        // `opt` and `syntax` may come before the output they apply to
+        if let Some(emit) = emit {
+            mode = Mode::Asm(AsmOptions { emit, ..asm });
+        } else if let Some(option) = asm_only {
+            return Err(OptionError::AsmOnly(option.to_string()));
+        }
+
+        Ok(Self { mode, color, split })
+    }
+}
+
+/// Rewrites the first line starting with `prefix` to start with [`DEFAULT_PREFIX`] instead, so
+/// [`match_code`] finds the code behind a guild's own prefix.
+///
+/// Returns `None` if no line starts with `prefix`. Lines starting with the default prefix before
+/// it are escaped, as the default prefix doesn't run code where another one is set.
+pub fn replace_prefix<'a>(input: &'a str, prefix: &str) -> Option<Cow<'a, str>> {
+    if prefix == DEFAULT_PREFIX {
+        return Some(Cow::Borrowed(input));
+    }
+
+    let mut out = String::with_capacity(input.len());
+    let mut replaced = false;
+    for line in input.split_inclusive('\n') {
+        if replaced {
+            out.push_str(line);
+        } else if line.starts_with(DEFAULT_PREFIX) {
+            // checked first, so a prefix like `$` doesn't turn `$>` into `$>>`
+            out.push('\\');
+            out.push_str(line);
+        } else if let Some(rest) = line.strip_prefix(prefix) {
+            out.push_str(DEFAULT_PREFIX);
+            out.push_str(rest);
+            replaced = true;
+        } else {
+            out.push_str(line);
+        }
+    }
+
+    replaced.then_some(Cow::Owned(out))
+}
+
+/// Whether `prefix` shares characters with the ends of [`DEFAULT_PREFIX`], like `$` or `>`, so
+/// messages using one could be read as using the other.
+pub fn overlaps_default_prefix(prefix: &str) -> bool {
+    prefix.contains(DEFAULT_PREFIX)
+        || DEFAULT_PREFIX.contains(prefix)
+        || (1..DEFAULT_PREFIX.len()).any(|len| {
+            prefix.ends_with(&DEFAULT_PREFIX[..len])
+                || prefix.starts_with(&DEFAULT_PREFIX[DEFAULT_PREFIX.len() - len..])
+        })
 }
 
 pub fn match_code(input: &str, no_prefix: bool) -> Option<MatchedCode<'_>> {
-    let Some(codeblock) = match_codeblock(input, no_prefix) else {
-        return match_inline_code(input, no_prefix);
+    let mut code = match match_codeblock(input, no_prefix) {
+        Some(codeblock) => codeblock,
+        None => match_inline_code(input, no_prefix)?,
     };
+    (code.stdin, code.cases) = match_io_blocks(input);
+
+    Some(code)
+}
 
-    Some(codeblock)
+/// Matches the ` ```in ` and ` ```out ` blocks of a message.
+///
+/// Returns the first input, and the test cases formed by every output with the input before it.
+pub fn match_io_blocks(input: &str) -> (Option<&str>, Vec<TestCase<'_>>) {
+    static RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"(?m)^```(?<kind>in|out)[ \t]*\n(?<content>[\S\s]*?)\n?```$").unwrap()
+    });
+
+    let mut stdin = None;
+    let mut cases = vec![];
+    let mut last_input = None;
+    for captures in RE.captures_iter(input) {
+        let (Some(kind), Some(content)) = (captures.name("kind"), captures.name("content")) else {
+            continue;
+        };
+
+        if kind.as_str() == "in" {
+            stdin = stdin.or(Some(content.as_str()));
+            last_input = Some(content.as_str());
+        } else {
+            cases.push(TestCase {
+                input: last_input.take().unwrap_or_default(),
+                expected: content.as_str(),
+            });
+        }
+    }
+
+    (stdin, cases)
 }
 
 pub fn match_codeblock(input: &str, no_prefix: bool) -> Option<MatchedCode<'_>> {
     static RE: Lazy<Regex> = Lazy::new(|| {
-        Regex::new(r"(?m)^\$>```(?<language>[a-zA-Z]*?)\s(?<code>[\S\s]*?)\s```$").unwrap()
+        Regex::new(r"(?m)^\$>```(?<language>[a-zA-Z]*?)(?:(?<options>(?:[ \t]+[a-z]+(?:=[\w.-]+)?)+)[ \t]*\n|\s)(?<code>[\S\s]*?)\s```$").unwrap()
     });
     static RE_NO_PREFIX: Lazy<Regex> = Lazy::new(|| {
-        Regex::new(r"(?m)```(?<language>[a-zA-Z]*?)\s(?<code>[\S\s]*?)\s```").unwrap()
+        Regex::new(r"(?m)```(?<language>[a-zA-Z]*?)(?:(?<options>(?:[ \t]+[a-z]+(?:=[\w.-]+)?)+)[ \t]*\n|\s)(?<code>[\S\s]*?)\s```").unwrap()
     });
 
     let captures = if no_prefix {
@@ -29,11 +259,17 @@ pub fn match_codeblock(input: &str, no_prefix: bool) -> Option<MatchedCode<'_>>
         RE.captures(input)?
     };
     let language = captures.name("language")?;
+    let options = captures
+        .name("options")
+        .map_or("", |options| options.as_str());
     let code = captures.name("code")?;
 
     Some(MatchedCode {
         language: language.as_str(),
+        options,
         code: code.as_str(),
+        stdin: None,
+        cases: vec![],
     })
 }
 
@@ -53,6 +289,179 @@ pub fn match_inline_code(input: &str, no_prefix: bool) -> Option<MatchedCode<'_>
 
     Some(MatchedCode {
         language: language.as_str(),
+        options: "",
         code: code.as_str(),
+        stdin: None,
+        cases: vec![],
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(options: &str) -> MatchedCode<'_> {
+        MatchedCode {
+            language: "rust",
+            options,
+            code: "",
+            stdin: None,
+            cases: vec![],
+        }
+    }
+
+    #[test]
+    fn runs_without_options() {
+        let options = Options::parse(&code("")).unwrap();
+
+        assert_eq!(options.mode, Mode::Run);
+        assert!(!options.color && !options.split);
+    }
+
+    #[test]
+    fn rejects_invalid_options() {
+        assert!(matches!(
+            Options::parse(&code("nope")),
+            Err(OptionError::Unknown(option)) if option == "nope"
+        ));
+        assert!(matches!(
+            Options::parse(&code("bench=0")),
+            Err(OptionError::InvalidValue { option, .. }) if option == "bench"
+        ));
+        assert!(matches!(
+            Options::parse(&code("bench=many")),
+            Err(OptionError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn matches_code_with_options() {
+        let code = match_code("$>```rust bench=5\nfn main() {}\n```", false).unwrap();
+
+        assert_eq!(code.language, "rust");
+        assert_eq!(code.options.trim(), "bench=5");
+        assert_eq!(code.code, "fn main() {}");
+    }
+
+    #[test]
+    fn judges_only_with_test_cases() {
+        assert!(matches!(
+            Options::parse(&code("judge")),
+            Err(OptionError::NoTestCases)
+        ));
+        assert!(matches!(
+            Options::parse(&code("judge=all")),
+            Err(OptionError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn matches_io_blocks() {
+        let (stdin, cases) =
+            match_io_blocks("```in\n1 2\n```\n```out\n3\n```\n```in\n4\n```\n```out\n5\n```");
+
+        assert_eq!(stdin, Some("1 2"));
+        assert_eq!(cases.len(), 2);
+        assert_eq!((cases[0].input, cases[0].expected), ("1 2", "3"));
+        assert_eq!((cases[1].input, cases[1].expected), ("4", "5"));
+    }
+
+    #[test]
+    fn matches_outputs_without_inputs() {
+        let (stdin, cases) = match_io_blocks("```out\nhello\n```");
+
+        assert_eq!(stdin, None);
+        assert_eq!((cases[0].input, cases[0].expected), ("", "hello"));
+    }
+
+    #[test]
+    fn parses_check() {
+        assert_eq!(Options::parse(&code("check")).unwrap().mode, Mode::Check);
+        assert!(matches!(
+            Options::parse(&code("check=yes")),
+            Err(OptionError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn parses_asm_options_in_any_order() {
+        let options = Options::parse(&code("opt=0 ir syntax=att")).unwrap();
+
+        assert_eq!(
+            options.mode,
+            Mode::Asm(AsmOptions {
+                emit: Emit::Ir,
+                opt_level: 0,
+                syntax: Syntax::Att,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_asm_options() {
+        assert!(matches!(
+            Options::parse(&code("asm opt=4")),
+            Err(OptionError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            Options::parse(&code("asm syntax=nasm")),
+            Err(OptionError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            Options::parse(&code("opt=1")),
+            Err(OptionError::AsmOnly(option)) if option == "opt"
+        ));
+    }
+
+    #[test]
+    fn parses_color() {
+        let options = Options::parse(&code("check color")).unwrap();
+
+        assert_eq!(options.mode, Mode::Check);
+        assert!(options.color);
+    }
+
+    #[test]
+    fn parses_split() {
+        let options = Options::parse(&code("split")).unwrap();
+
+        assert_eq!(options.mode, Mode::Run);
+        assert!(options.split);
+    }
+
+    #[test]
+    fn replaces_prefix() {
+        assert!(matches!(
+            replace_prefix("$>`a`", DEFAULT_PREFIX),
+            Some(Cow::Borrowed("$>`a`"))
+        ));
+        assert_eq!(
+            replace_prefix("hi\n!run`a`\n!run`b`", "!run").as_deref(),
+            Some("hi\n$>`a`\n!run`b`")
+        );
+        assert_eq!(
+            replace_prefix("$>`a`\n!run`b`", "!run").as_deref(),
+            Some("\\$>`a`\n$>`b`")
+        );
+        assert_eq!(replace_prefix("$>`a`", "!run"), None);
+    }
+
+    #[test]
+    fn keeps_the_default_prefix_behind_an_overlapping_prefix() {
+        assert_eq!(replace_prefix("$>```rust\nfn main() {}\n```", "$"), None);
+        assert_eq!(
+            replace_prefix("$>`a`\n$`b`", "$").as_deref(),
+            Some("\\$>`a`\n$>`b`")
+        );
+    }
+
+    #[test]
+    fn finds_prefixes_overlapping_the_default() {
+        for prefix in ["$", ">", "$>", "!$", ">!", "$>>", "a$>b"] {
+            assert!(overlaps_default_prefix(prefix), "{prefix}");
+        }
+        for prefix in ["!run", "%", "?>", "$!"] {
+            assert!(!overlaps_default_prefix(prefix), "{prefix}");
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use sqlx::{types::time::OffsetDateTime, PgPool};
+use twilight_model::id::{
+    marker::{GuildMarker, UserMarker},
+    Id,
+};
+
+use crate::config::CONFIG;
+
+/// How often buckets that filled up again are dropped, along with the runs that are too old to
+/// matter.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Who a rate limit applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Scope {
+    User,
+    Guild,
+}
+
+impl Scope {
+    fn limit(self) -> Limit {
+        match self {
+            Scope::User => Limit {
+                burst: f64::from(CONFIG.rate_limit_user_burst),
+                refill: Duration::from_millis(CONFIG.rate_limit_user_refill_ms),
+            },
+            Scope::Guild => Limit {
+                burst: f64::from(CONFIG.rate_limit_guild_burst),
+                refill: Duration::from_millis(CONFIG.rate_limit_guild_refill_ms),
+            },
+        }
+    }
+}
+
+/// The size of a bucket and how quickly it refills.
+#[derive(Debug, Clone, Copy)]
+struct Limit {
+    /// How many executions can run in quick succession
+    burst: f64,
+    /// How long it takes to be allowed one more execution
+    refill: Duration,
+}
+
+impl Limit {
+    /// How long an empty bucket takes to fill up.
+    fn window(&self) -> Duration {
+        self.refill.mul_f64(self.burst)
+    }
+}
+
+/// A token bucket: every execution takes a token, and tokens come back one per refill period.
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn full(limit: Limit, now: Instant) -> Self {
+        Self {
+            tokens: limit.burst,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, limit: Limit, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated);
+        self.tokens =
+            (self.tokens + elapsed.as_secs_f64() / limit.refill.as_secs_f64()).min(limit.burst);
+        self.updated = now;
+    }
+
+    /// How long until the bucket has a token, zero if it has one now.
+    fn wait(&self, limit: Limit) -> Duration {
+        if self.tokens >= 1.0 {
+            return Duration::ZERO;
+        }
+
+        limit.refill.mul_f64(1.0 - self.tokens)
+    }
+
+    fn is_full(&self, limit: Limit) -> bool {
+        self.tokens >= limit.burst
+    }
+
+    /// Rebuilds a bucket from the times tokens were taken, oldest first, as if it was full at
+    /// `since`.
+    fn replay(limit: Limit, since: Instant, runs: impl IntoIterator<Item = Instant>) -> Self {
+        let mut bucket = Bucket::full(limit, since);
+        for run in runs {
+            bucket.refill(limit, run);
+            bucket.tokens = (bucket.tokens - 1.0).max(0.0);
+        }
+
+        bucket
+    }
+}
+
+/// Token bucket rate limits of the executions of users and guilds.
+///
+/// Buckets are kept in memory. Every execution that takes a token is also recorded in the
+/// database, and the first time a bucket is needed it's derived from those, so a restart doesn't
+/// reset the limits.
+#[derive(Default)]
+pub struct RateLimits {
+    buckets: Mutex<HashMap<(Scope, String), Bucket>>,
+    pruned_at: Mutex<Option<Instant>>,
+}
+
+impl RateLimits {
+    /// Takes a token for an execution by `user` in `guild`.
+    ///
+    /// Returns how long to wait if either of them is out of tokens, in which case none are taken.
+    pub async fn acquire(
+        &self,
+        db: &PgPool,
+        user: Option<Id<UserMarker>>,
+        guild: Option<Id<GuildMarker>>,
+    ) -> anyhow::Result<Option<Duration>> {
+        if user.is_some_and(|user| CONFIG.owner_ids.contains(&user)) {
+            return Ok(None);
+        }
+
+        let keys = [
+            user.map(|user| (Scope::User, user.to_string())),
+            guild.map(|guild| (Scope::Guild, guild.to_string())),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+        let mut derived = vec![];
+        for key in &keys {
+            if !self.buckets.lock().unwrap().contains_key(key) {
+                derived.push((key.clone(), derive(db, key).await?));
+            }
+        }
+
+        let now = Instant::now();
+        {
+            let mut buckets = self.buckets.lock().unwrap();
+            for (key, bucket) in derived {
+                buckets.entry(key).or_insert(bucket);
+            }
+
+            let mut wait = Duration::ZERO;
+            for key in &keys {
+                let bucket = buckets.get_mut(key).expect("bucket was just inserted");
+                bucket.refill(key.0.limit(), now);
+                wait = wait.max(bucket.wait(key.0.limit()));
+            }
+            if !wait.is_zero() {
+                return Ok(Some(wait));
+            }
+
+            for key in &keys {
+                if let Some(bucket) = buckets.get_mut(key) {
+                    bucket.tokens -= 1.0;
+                }
+            }
+        }
+
+        sqlx::query!(
+            "insert into rate_limit_run (user_id, guild_id) values ($1, $2)",
+            user.map(|user| user.to_string()),
+            guild.map(|guild| guild.to_string()),
+        )
+        .execute(db)
+        .await?;
+
+        if self.should_prune(now) {
+            self.prune(db, now).await?;
+        }
+
+        Ok(None)
+    }
+
+    fn should_prune(&self, now: Instant) -> bool {
+        let mut pruned_at = self.pruned_at.lock().unwrap();
+        if matches!(*pruned_at, Some(at) if now.duration_since(at) < PRUNE_INTERVAL) {
+            return false;
+        }
+        *pruned_at = Some(now);
+
+        true
+    }
+
+    /// Drops the buckets that filled up again, as deriving them would give a full bucket too,
+    /// and the runs older than the longest window.
+    async fn prune(&self, db: &PgPool, now: Instant) -> anyhow::Result<()> {
+        self.buckets.lock().unwrap().retain(|(scope, _), bucket| {
+            bucket.refill(scope.limit(), now);
+            !bucket.is_full(scope.limit())
+        });
+
+        let window = Scope::User
+            .limit()
+            .window()
+            .max(Scope::Guild.limit().window());
+        sqlx::query!(
+            "delete from rate_limit_run where ran_at < $1",
+            OffsetDateTime::now_utc() - window
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Rebuilds a bucket from the runs recorded in the time it takes to refill completely.
+async fn derive(db: &PgPool, (scope, id): &(Scope, String)) -> anyhow::Result<Bucket> {
+    let limit = scope.limit();
+    let now = OffsetDateTime::now_utc();
+    let since = now - limit.window();
+    let runs = match scope {
+        Scope::User => {
+            sqlx::query_scalar!(
+                "select ran_at from rate_limit_run
+                where user_id = $1 and ran_at > $2 order by ran_at",
+                id,
+                since
+            )
+            .fetch_all(db)
+            .await?
+        }
+        Scope::Guild => {
+            sqlx::query_scalar!(
+                "select ran_at from rate_limit_run
+                where guild_id = $1 and ran_at > $2 order by ran_at",
+                id,
+                since
+            )
+            .fetch_all(db)
+            .await?
+        }
+    };
+
+    let instant_now = Instant::now();
+    let at = |time: OffsetDateTime| {
+        let ago = Duration::try_from(now - time).unwrap_or_default();
+        instant_now.checked_sub(ago).unwrap_or(instant_now)
+    };
+
+    Ok(Bucket::replay(limit, at(since), runs.into_iter().map(at)))
+}
+
+/// The reply to an execution that was rate limited.
+pub fn retry_message(wait: Duration) -> String {
+    format!(
+        "You're running code too quickly, try again in {}s.",
+        wait.as_secs_f64().ceil()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMIT: Limit = Limit {
+        burst: 3.0,
+        refill: Duration::from_secs(10),
+    };
+
+    fn take(bucket: &mut Bucket, now: Instant) -> Duration {
+        bucket.refill(LIMIT, now);
+        let wait = bucket.wait(LIMIT);
+        if wait.is_zero() {
+            bucket.tokens -= 1.0;
+        }
+
+        wait
+    }
+
+    #[test]
+    fn allows_a_burst_then_waits() {
+        let now = Instant::now();
+        let mut bucket = Bucket::full(LIMIT, now);
+
+        for _ in 0..3 {
+            assert_eq!(take(&mut bucket, now), Duration::ZERO);
+        }
+        assert_eq!(take(&mut bucket, now), Duration::from_secs(10));
+        assert_eq!(
+            take(&mut bucket, now + Duration::from_secs(4)),
+            Duration::from_secs(6)
+        );
+        assert_eq!(
+            take(&mut bucket, now + Duration::from_secs(10)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn refills_up_to_the_burst() {
+        let now = Instant::now();
+        let mut bucket = Bucket::full(LIMIT, now);
+        take(&mut bucket, now);
+
+        bucket.refill(LIMIT, now + Duration::from_secs(3600));
+        assert!(bucket.is_full(LIMIT));
+        assert_eq!(bucket.tokens, LIMIT.burst);
+    }
+
+    #[test]
+    fn replays_every_run() {
+        let now = Instant::now();
+        let since = now - LIMIT.window();
+        let bucket = Bucket::replay(LIMIT, since, [now, now, now]);
+
+        assert_eq!(bucket.wait(LIMIT), Duration::from_secs(10));
+        assert!(!bucket.is_full(LIMIT));
+
+        let bucket = Bucket::replay(LIMIT, since, [since]);
+        let mut refilled = bucket;
+        refilled.refill(LIMIT, now);
+        assert!(refilled.is_full(LIMIT));
+    }
+}
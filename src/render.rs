@@ -0,0 +1,256 @@
+//! Rendering of program output for Discord messages.
+
+use docker_api::conn::TtyChunk;
+use twilight_model::http::attachment::Attachment;
+
+use crate::{
+    config::CONFIG,
+    hypervisor::{ansi, languages::Languages},
+};
+
+/// How many characters of output are shown in the message itself.
+pub const MAX_INLINE_OUTPUT: usize = 1500;
+/// How many pages output may span before it's attached instead.
+pub const MAX_PAGES: usize = 10;
+
+/// Output rendered for a message.
+#[derive(Debug)]
+pub struct Rendered {
+    /// A preview of the output, or its first page
+    pub content: String,
+    /// The full output, if it didn't fit into the preview
+    pub attachments: Vec<Attachment>,
+    /// Every page of the output, if it was split into pages
+    pub pages: Vec<String>,
+}
+
+/// Output split into pages that are shown one at a time, above a footer shared by all pages.
+#[derive(Debug, Clone)]
+pub struct Paged {
+    pub pages: Vec<String>,
+    pub footer: String,
+}
+
+impl Paged {
+    /// The content of the message showing page `page`.
+    pub fn page(&self, page: usize) -> String {
+        let page = self.pages.get(page).map_or("", String::as_str);
+        if self.footer.is_empty() {
+            page.to_string()
+        } else {
+            format!("{page}\n{}", self.footer)
+        }
+    }
+}
+
+/// Renders the output of a program.
+///
+/// Output that's too long to show inline is split into up to [`MAX_PAGES`] pages. Anything
+/// longer is attached in full as `output.txt`, or as `stdout.txt` and `stderr.txt` with
+/// `split`, which also skips the pages.
+pub fn render_output(language: &Languages, output: &[TtyChunk], split: bool) -> Rendered {
+    let text = output_text(output);
+    if text.chars().count() <= MAX_INLINE_OUTPUT {
+        return Rendered {
+            content: preview(language, &text),
+            attachments: vec![],
+            pages: vec![],
+        };
+    }
+
+    let pages = paginate(&text);
+    if !split && pages.len() <= MAX_PAGES {
+        let pages = pages
+            .into_iter()
+            .map(|page| code_block(language, page))
+            .collect::<Vec<_>>();
+
+        return Rendered {
+            content: pages[0].clone(),
+            attachments: vec![],
+            pages,
+        };
+    }
+
+    let content = format!("{}\n-# 📎 Full output attached", preview(language, &text));
+    let files = if split {
+        let stdout = text_of(
+            output
+                .iter()
+                .filter(|chunk| matches!(chunk, TtyChunk::StdOut(_))),
+        );
+        let stderr = text_of(
+            output
+                .iter()
+                .filter(|chunk| matches!(chunk, TtyChunk::StdErr(_))),
+        );
+        vec![("stdout.txt", stdout), ("stderr.txt", stderr)]
+    } else {
+        vec![("output.txt", text)]
+    };
+    let attachments = files
+        .into_iter()
+        .filter(|(_, text)| !text.is_empty())
+        .enumerate()
+        .map(|(id, (filename, text))| {
+            attachment(filename.to_string(), &ansi::strip(&text), id as u64)
+        })
+        .collect();
+
+    Rendered {
+        content,
+        attachments,
+        pages: vec![],
+    }
+}
+
+/// Splits `text` into pages of at most [`MAX_INLINE_OUTPUT`] characters, breaking between
+/// lines where possible.
+pub fn paginate(text: &str) -> Vec<&str> {
+    let mut pages = vec![];
+    let mut rest = text;
+    while !rest.is_empty() {
+        let (page, cut) = truncate(rest, MAX_INLINE_OUTPUT);
+        let end = match page.rfind('\n') {
+            Some(newline) if cut > 0 => newline + 1,
+            _ => page.len(),
+        };
+        pages.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+
+    pages
+}
+
+/// Renders `text` as a code block.
+///
+/// Colored output goes into an ` ```ansi ` block, anything else is highlighted as `language`
+/// with escape sequences stripped.
+pub fn code_block(language: &Languages, text: &str) -> String {
+    if text.is_empty() {
+        format!("```{language}\nNo output\n```")
+    } else if ansi::has_colors(text) {
+        format!("```ansi\n{}\n```", ansi::translate(text))
+    } else {
+        format!("```{language}\n{}\n```", ansi::strip(text))
+    }
+}
+
+/// Renders `text` as a code block, cut down to [`MAX_INLINE_OUTPUT`] characters.
+pub fn preview(language: &Languages, text: &str) -> String {
+    let (shown, rest) = truncate(text, MAX_INLINE_OUTPUT);
+    if rest == 0 {
+        return code_block(language, shown);
+    }
+
+    code_block(language, &format!("{shown}...({rest} more characters)"))
+}
+
+/// Attaches `text` as `filename`, cut down to the configured attachment size.
+pub fn attachment(filename: String, text: &str, id: u64) -> Attachment {
+    let text = truncate_bytes(text, CONFIG.max_attachment_size);
+
+    Attachment::from_bytes(filename, text.as_bytes().to_vec(), id)
+}
+
+/// Everything a program printed, in order.
+pub fn output_text(output: &[TtyChunk]) -> String {
+    text_of(output)
+}
+
+fn text_of<'a>(chunks: impl IntoIterator<Item = &'a TtyChunk>) -> String {
+    // chunks may end in the middle of a character, so they're decoded together
+    let bytes = chunks
+        .into_iter()
+        .flat_map(|chunk| match chunk {
+            TtyChunk::StdIn(bytes) | TtyChunk::StdOut(bytes) | TtyChunk::StdErr(bytes) => bytes,
+        })
+        .copied()
+        .collect::<Vec<_>>();
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Cuts `text` down to at most `max` characters, returning how many characters were cut.
+pub fn truncate(text: &str, max: usize) -> (&str, usize) {
+    match text.char_indices().nth(max) {
+        Some((end, _)) => (&text[..end], text[end..].chars().count()),
+        None => (text, 0),
+    }
+}
+
+/// Cuts `text` down to at most `max` bytes without splitting a character.
+pub fn truncate_bytes(text: &str, max: usize) -> &str {
+    if text.len() <= max {
+        return text;
+    }
+
+    let end = (0..=max)
+        .rev()
+        .find(|&end| text.is_char_boundary(end))
+        .unwrap_or(0);
+
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_characters() {
+        assert_eq!(truncate("héllo", 2), ("hé", 3));
+        assert_eq!(truncate("héllo", 5), ("héllo", 0));
+        assert_eq!(truncate("", 0), ("", 0));
+    }
+
+    #[test]
+    fn truncates_bytes_on_char_boundaries() {
+        assert_eq!(truncate_bytes("héllo", 2), "h");
+        assert_eq!(truncate_bytes("héllo", 3), "hé");
+        assert_eq!(truncate_bytes("héllo", 100), "héllo");
+    }
+
+    #[test]
+    fn decodes_characters_split_across_chunks() {
+        let output = [
+            TtyChunk::StdOut(vec![b'h', 0xc3]),
+            TtyChunk::StdErr(vec![0xa9]),
+        ];
+
+        assert_eq!(output_text(&output), "hé");
+    }
+
+    #[test]
+    fn paginates_between_lines() {
+        let line = format!("{}\n", "a".repeat(99));
+        let text = line.repeat(20);
+        let pages = paginate(&text);
+
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].len(), 1500);
+        assert!(pages.iter().all(|page| page.ends_with('\n')));
+        assert_eq!(pages.concat(), text);
+    }
+
+    #[test]
+    fn paginates_long_lines() {
+        let text = "é".repeat(MAX_INLINE_OUTPUT * 2 + 1);
+        let pages = paginate(&text);
+
+        assert_eq!(pages.len(), 3);
+        assert_eq!(pages[0].chars().count(), MAX_INLINE_OUTPUT);
+        assert_eq!(pages.concat(), text);
+    }
+
+    #[test]
+    fn shows_the_footer_below_every_page() {
+        let paged = Paged {
+            pages: vec!["one".to_string(), "two".to_string()],
+            footer: "-# done".to_string(),
+        };
+
+        assert_eq!(paged.page(1), "two\n-# done");
+        assert_eq!(paged.page(2), "\n-# done");
+    }
+}
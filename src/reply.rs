@@ -0,0 +1,394 @@
+use std::time::Duration;
+
+use docker_api::conn::TtyChunk;
+use futures::StreamExt;
+use sqlx::PgPool;
+use tokio_util::sync::CancellationToken;
+use twilight_http::{client::InteractionClient, Client};
+use twilight_model::{
+    channel::message::Component,
+    http::attachment::Attachment,
+    id::{
+        marker::{ChannelMarker, MessageMarker},
+        Id,
+    },
+};
+
+use crate::{
+    hypervisor::{
+        bench, check,
+        exec_error::ExecError,
+        exit_status::ExitStatus,
+        judge::{self, Verdict},
+        languages::Languages,
+        Finished, Hypervisor, RunOptions,
+    },
+    metrics,
+    parsers::{MatchedCode, Mode, Options},
+    render::{self, render_output, Paged, Rendered},
+};
+
+/// How often the reply of a running execution is edited to show its output so far.
+const EDIT_INTERVAL: Duration = Duration::from_secs(2);
+/// How long compiler output may be before it's sent as a file instead.
+const MAX_INLINE_ASM: usize = 1800;
+
+/// The message an execution reports to.
+pub enum Reply<'a> {
+    /// A message the bot sent in reply to a `$>` message
+    Message {
+        http: &'a Client,
+        channel_id: Id<ChannelMarker>,
+        message_id: Id<MessageMarker>,
+    },
+    /// The original response to an interaction
+    Interaction {
+        client: &'a InteractionClient<'a>,
+        token: &'a str,
+    },
+}
+
+impl Reply<'_> {
+    pub async fn edit(
+        &self,
+        content: &str,
+        components: &[Component],
+    ) -> Result<(), twilight_http::Error> {
+        self.edit_with_attachments(content, components, &[]).await
+    }
+
+    /// Edits the reply, replacing its attachments with `attachments`.
+    pub async fn edit_with_attachments(
+        &self,
+        content: &str,
+        components: &[Component],
+        attachments: &[Attachment],
+    ) -> Result<(), twilight_http::Error> {
+        let res = match self {
+            Reply::Message {
+                http,
+                channel_id,
+                message_id,
+            } => {
+                http.update_message(*channel_id, *message_id)
+                    .content(Some(content))
+                    .components(Some(components))
+                    .attachments(attachments)
+                    .keep_attachment_ids(&[])
+                    .await
+            }
+            Reply::Interaction { client, token } => {
+                client
+                    .update_response(token)
+                    .content(Some(content))
+                    .components(Some(components))
+                    .attachments(attachments)
+                    .keep_attachment_ids(&[])
+                    .await
+            }
+        };
+
+        res.map(|_| ()).inspect_err(metrics::record_http_error)
+    }
+}
+
+/// Executes `code`, periodically editing `reply` to show the output so far.
+///
+/// `components` are kept on the reply while the program is running. Returns the output and how
+/// the program finished.
+pub async fn exec_live(
+    reply: &Reply<'_>,
+    hypervisor: &Hypervisor,
+    language: &Languages,
+    code: &str,
+    options: RunOptions<'_>,
+    cancel: &CancellationToken,
+    components: &[Component],
+) -> Result<(Vec<TtyChunk>, Option<Finished>), ExecError> {
+    let mut stream = hypervisor
+        .exec_stream(language, code, options, cancel)
+        .await?;
+    let mut output = vec![];
+    let mut changed = false;
+    let mut interval = tokio::time::interval(EDIT_INTERVAL);
+    // the first tick completes immediately, but there is nothing to show yet
+    interval.tick().await;
+
+    loop {
+        tokio::select! {
+            chunk = stream.next() => match chunk {
+                Some(chunk) => {
+                    output.push(chunk?);
+                    changed = true;
+                }
+                None => break,
+            },
+            _ = interval.tick(), if changed => {
+                changed = false;
+
+                let out = render::preview(language, &render::output_text(&output));
+                let content = format!("{out}\n-# ⏳ Still running...");
+                if let Err(e) = reply.edit(&content, components).await {
+                    tracing::error!("failed to edit reply - {e}");
+                }
+            }
+        }
+    }
+
+    Ok((output, stream.finished().await))
+}
+
+/// How an execution ended, as stored in the `execution` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// The program ran and exited successfully
+    Success,
+    /// The program exited with a non-zero code, was killed or was judged wrong
+    Failure,
+    /// The program could not be built
+    CompileFailure,
+    /// The program was killed after exceeding the time limit
+    Timeout,
+    /// Something went wrong on our side
+    Error,
+}
+
+impl Status {
+    /// Reads a status stored in the `execution` table.
+    pub fn parse(status: &str) -> Option<Self> {
+        let status = match status {
+            "success" => Self::Success,
+            "failure" => Self::Failure,
+            "compile_failure" => Self::CompileFailure,
+            "timeout" => Self::Timeout,
+            "error" => Self::Error,
+            _ => return None,
+        };
+
+        Some(status)
+    }
+
+    /// The name of the status as stored in the `execution` table.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Failure => "failure",
+            Self::CompileFailure => "compile_failure",
+            Self::Timeout => "timeout",
+            Self::Error => "error",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Success => "✅ Success",
+            Self::Failure => "❌ Failed",
+            Self::CompileFailure => "🛠️ Compilation failed",
+            Self::Timeout => "⏱️ Timed out",
+            Self::Error => "⚠️ Error",
+        }
+    }
+
+    fn of_error(error: &ExecError) -> Self {
+        match error {
+            ExecError::Timeout => Self::Timeout,
+            ExecError::OutOfMemory => Self::Failure,
+            _ => Self::Error,
+        }
+    }
+
+    fn of_exit(status: Option<ExitStatus>) -> Self {
+        match status {
+            Some(ExitStatus::Success) => Self::Success,
+            Some(ExitStatus::CompileFailure) => Self::CompileFailure,
+            Some(_) => Self::Failure,
+            // the run ended without `run.sh` reporting how it went
+            None => Self::Error,
+        }
+    }
+}
+
+/// A finished execution.
+pub struct Executed {
+    /// What the reply should show
+    pub content: String,
+    /// Files attached to the reply
+    pub attachments: Vec<Attachment>,
+    /// How the execution ended
+    pub status: Status,
+    /// The verdict of a judged execution
+    pub verdict: Option<Verdict>,
+    /// The pages of long output, `content` showing the first one
+    pub paged: Option<Paged>,
+}
+
+impl Executed {
+    fn new(content: String) -> Self {
+        Self {
+            content,
+            attachments: vec![],
+            status: Status::Success,
+            verdict: None,
+            paged: None,
+        }
+    }
+
+    /// Shows the output of a program above `footer`, paginated if it was split into pages.
+    fn output(rendered: Rendered, footer: String, status: Status) -> Self {
+        let content = if footer.is_empty() {
+            rendered.content
+        } else {
+            format!("{}\n{footer}", rendered.content)
+        };
+        let paged = (!rendered.pages.is_empty()).then(|| Paged {
+            pages: rendered.pages,
+            footer,
+        });
+
+        Self {
+            content,
+            attachments: rendered.attachments,
+            status,
+            verdict: None,
+            paged,
+        }
+    }
+
+    /// Adds a line to the bottom of the reply, below every page.
+    pub fn push_line(&mut self, line: &str) {
+        self.content.push('\n');
+        self.content.push_str(line);
+        if let Some(paged) = &mut self.paged {
+            if !paged.footer.is_empty() {
+                paged.footer.push('\n');
+            }
+            paged.footer.push_str(line);
+        }
+    }
+}
+
+/// Executes `code` as `options` say and returns what `reply` should show once it's done.
+///
+/// Plain runs show their output live through [`exec_live`]; the other modes only report at the
+/// end.
+pub async fn execute(
+    reply: &Reply<'_>,
+    hypervisor: &Hypervisor,
+    language: &Languages,
+    code: &MatchedCode<'_>,
+    options: Options,
+    cancel: &CancellationToken,
+    components: &[Component],
+) -> Result<Executed, ExecError> {
+    match options.mode {
+        Mode::Run => {
+            let run_options = RunOptions {
+                stdin: code.stdin,
+                color: options.color,
+            };
+            let (output, finished) = exec_live(
+                reply,
+                hypervisor,
+                language,
+                code.code,
+                run_options,
+                cancel,
+                components,
+            )
+            .await?;
+            let status = Status::of_exit(finished.and_then(|finished| finished.status));
+            let footer = match finished.map(|finished| (finished.status, finished.usage)) {
+                Some((Some(ExitStatus::CompileFailure), _)) => {
+                    "-# 🛠️ Compilation failed".to_string()
+                }
+                Some((Some(status), usage)) if !status.success() => {
+                    format!("-# ❌ {status} • {usage}")
+                }
+                Some((_, usage)) => format!("-# ✅ {usage}"),
+                None => String::new(),
+            };
+
+            Ok(Executed::output(
+                render_output(language, &output, options.split),
+                footer,
+                status,
+            ))
+        }
+        Mode::Bench(runs) => {
+            let report = hypervisor.bench(language, code.code, runs, cancel).await?;
+            Ok(Executed::output(
+                render_output(language, &report.output, options.split),
+                bench::format_report(&report),
+                Status::of_exit(report.status),
+            ))
+        }
+        Mode::Check => {
+            let report = hypervisor.check(language, code.code, cancel).await?;
+
+            Ok(Executed::new(check::format_report(&report)))
+        }
+        Mode::Judge => {
+            let report = hypervisor
+                .judge(language, code.code, &code.cases, cancel)
+                .await?;
+
+            let verdict = report.verdict();
+            let status = match verdict {
+                Verdict::Accepted => Status::Success,
+                Verdict::WrongAnswer => Status::Failure,
+                Verdict::TimeLimitExceeded => Status::Timeout,
+                Verdict::CompilationError => Status::CompileFailure,
+            };
+
+            Ok(Executed {
+                status,
+                verdict: Some(verdict),
+                ..Executed::new(judge::format_report(&report, &code.cases))
+            })
+        }
+        Mode::Asm(asm) => {
+            let output = hypervisor.asm(language, code.code, &asm, cancel).await?;
+            let highlight = asm.emit.highlight();
+            if output.len() <= MAX_INLINE_ASM {
+                return Ok(Executed::new(format!("```{highlight}\n{output}\n```")));
+            }
+
+            let filename = format!("output.{}", asm.emit.extension());
+            let lines = output.lines().count();
+
+            Ok(Executed {
+                attachments: vec![render::attachment(filename, &output, 0)],
+                ..Executed::new(format!("-# 📎 {lines} lines, see the attached file"))
+            })
+        }
+    }
+}
+
+/// Records how the execution with the database id `id` ended and how long it took, unless it
+/// was cancelled.
+pub async fn record(
+    db: &PgPool,
+    id: i64,
+    result: &Result<Executed, ExecError>,
+    duration: Duration,
+) -> anyhow::Result<()> {
+    let (status, verdict) = match result {
+        Ok(executed) => (executed.status, executed.verdict),
+        Err(ExecError::Cancelled) => return Ok(()),
+        Err(e) => (Status::of_error(e), None),
+    };
+    // a rerun that isn't judged clears the previous verdict
+    let verdict = verdict.map(|verdict| verdict.as_str());
+    let duration_ms = i32::try_from(duration.as_millis()).unwrap_or(i32::MAX);
+    sqlx::query!(
+        "update execution set status = $1, verdict = $2, duration_ms = $3 where id = $4",
+        status.as_str(),
+        verdict,
+        duration_ms,
+        id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
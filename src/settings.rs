@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use sqlx::PgPool;
+use twilight_model::id::{
+    marker::{ChannelMarker, GuildMarker},
+    Id,
+};
+
+use crate::{
+    hypervisor::languages::Languages,
+    parsers::{Options, DEFAULT_PREFIX},
+};
+
+/// How a guild configured the bot, see the `/settings` command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuildSettings {
+    /// What starts a message whose code is run automatically
+    pub prefix: String,
+    /// The languages that may be run, or `None` for every enabled language
+    pub languages: Option<Vec<String>>,
+    /// The channels code is run automatically in, or every channel if empty
+    pub allowed_channels: Vec<String>,
+    /// The channels code is never run automatically in
+    pub forbidden_channels: Vec<String>,
+    /// Whether editing a message runs its code again
+    pub rerun_on_edit: bool,
+    /// Whether long output is attached as separate stdout and stderr files by default
+    pub split_output: bool,
+}
+
+impl Default for GuildSettings {
+    fn default() -> Self {
+        Self {
+            prefix: DEFAULT_PREFIX.to_string(),
+            languages: None,
+            allowed_channels: vec![],
+            forbidden_channels: vec![],
+            rerun_on_edit: true,
+            split_output: false,
+        }
+    }
+}
+
+impl GuildSettings {
+    /// Whether code in `language` may be run.
+    pub fn allows_language(&self, language: &Languages) -> bool {
+        match &self.languages {
+            Some(languages) => languages.contains(&language.to_string()),
+            None => true,
+        }
+    }
+
+    /// Applies the defaults of the guild to the options given with code.
+    pub fn apply(&self, options: &mut Options) {
+        options.split |= self.split_output;
+    }
+
+    /// Whether `$>` messages in `channel` are run.
+    pub fn auto_runs_in(&self, channel: Id<ChannelMarker>) -> bool {
+        let channel = channel.to_string();
+        !self.forbidden_channels.contains(&channel)
+            && (self.allowed_channels.is_empty() || self.allowed_channels.contains(&channel))
+    }
+
+    async fn load(db: &PgPool, guild: Id<GuildMarker>) -> anyhow::Result<Self> {
+        let guild_id = guild.to_string();
+        let settings = sqlx::query!(
+            "select prefix, languages, allowed_channels, forbidden_channels, rerun_on_edit, split_output from guild_settings where guild_id = $1",
+            guild_id
+        )
+        .fetch_optional(db)
+        .await?
+        .map_or_else(Self::default, |row| Self {
+            prefix: row.prefix,
+            languages: row.languages,
+            allowed_channels: row.allowed_channels,
+            forbidden_channels: row.forbidden_channels,
+            rerun_on_edit: row.rerun_on_edit,
+            split_output: row.split_output,
+        });
+
+        Ok(settings)
+    }
+
+    async fn save(&self, db: &PgPool, guild: Id<GuildMarker>) -> anyhow::Result<()> {
+        let guild_id = guild.to_string();
+        sqlx::query!(
+            "insert into guild_settings (guild_id, prefix, languages, allowed_channels, forbidden_channels, rerun_on_edit, split_output) values ($1, $2, $3, $4, $5, $6, $7)
+            on conflict (guild_id) do update set prefix = excluded.prefix, languages = excluded.languages, allowed_channels = excluded.allowed_channels, forbidden_channels = excluded.forbidden_channels, rerun_on_edit = excluded.rerun_on_edit, split_output = excluded.split_output, updated_at = now()",
+            guild_id,
+            self.prefix,
+            self.languages.as_deref(),
+            &self.allowed_channels,
+            &self.forbidden_channels,
+            self.rerun_on_edit,
+            self.split_output
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// The settings of the guilds the bot has seen, loaded from the database on first use.
+#[derive(Default)]
+pub struct GuildSettingsCache {
+    settings: Mutex<HashMap<Id<GuildMarker>, Arc<GuildSettings>>>,
+}
+
+impl GuildSettingsCache {
+    /// The settings of `guild`, or the defaults outside of guilds.
+    pub async fn get(
+        &self,
+        db: &PgPool,
+        guild: Option<Id<GuildMarker>>,
+    ) -> anyhow::Result<Arc<GuildSettings>> {
+        let Some(guild) = guild else {
+            return Ok(Arc::default());
+        };
+        if let Some(settings) = self.settings.lock().unwrap().get(&guild) {
+            return Ok(settings.clone());
+        }
+
+        let settings = Arc::new(GuildSettings::load(db, guild).await?);
+        self.settings
+            .lock()
+            .unwrap()
+            .insert(guild, settings.clone());
+
+        Ok(settings)
+    }
+
+    /// Stores new settings for `guild`.
+    pub async fn set(
+        &self,
+        db: &PgPool,
+        guild: Id<GuildMarker>,
+        settings: GuildSettings,
+    ) -> anyhow::Result<()> {
+        settings.save(db, guild).await?;
+        self.settings
+            .lock()
+            .unwrap()
+            .insert(guild, Arc::new(settings));
+
+        Ok(())
+    }
+}
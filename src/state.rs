@@ -1,11 +1,198 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use sqlx::PgPool;
+use tokio_util::sync::CancellationToken;
+use twilight_model::id::{marker::GenericMarker, Id};
 
+use crate::blocklist::Blocklist;
+use crate::config::CONFIG;
 use crate::hypervisor::Hypervisor;
+use crate::rate_limit::RateLimits;
+use crate::render::Paged;
+use crate::settings::GuildSettingsCache;
 
 #[derive(Clone)]
 pub struct BotState {
     pub hypervisor: Arc<Hypervisor>,
     pub db: PgPool,
+    pub running: Arc<RunningExecutions>,
+    pub pages: Arc<PagedOutputs>,
+    pub settings: Arc<GuildSettingsCache>,
+    pub blocklist: Arc<Blocklist>,
+    pub rate_limits: Arc<RateLimits>,
+}
+
+/// Cancellation handles of the executions that are currently running.
+///
+/// Executions are keyed by whatever started them: the id of the source message for `$>`
+/// messages, or the id of the interaction for commands.
+#[derive(Default)]
+pub struct RunningExecutions {
+    generation: AtomicU64,
+    executions: Mutex<HashMap<Id<GenericMarker>, Running>>,
+}
+
+struct Running {
+    generation: u64,
+    /// The version of the code being executed, see [`RunningExecutions::start_version`]
+    version: i64,
+    token: CancellationToken,
+    /// Whether the execution was stopped by whoever started it, see [`RunningExecutions::stop`]
+    stopped: Arc<AtomicBool>,
+}
+
+impl RunningExecutions {
+    /// Registers a new execution under `key`, cancelling the one it supersedes (if any).
+    pub fn start(self: &Arc<Self>, key: Id<GenericMarker>) -> RunningExecution {
+        let mut executions = self.executions.lock().unwrap();
+        self.register(&mut executions, key, i64::MIN)
+    }
+
+    /// Like [`start`](Self::start), but leaves an execution of a newer `version` of the code
+    /// alone, like one started for a later edit of a message.
+    ///
+    /// Returns `None` if there is such an execution.
+    pub fn start_version(
+        self: &Arc<Self>,
+        key: Id<GenericMarker>,
+        version: i64,
+    ) -> Option<RunningExecution> {
+        let mut executions = self.executions.lock().unwrap();
+        if executions
+            .get(&key)
+            .is_some_and(|running| running.version > version)
+        {
+            return None;
+        }
+
+        Some(self.register(&mut executions, key, version))
+    }
+
+    fn register(
+        self: &Arc<Self>,
+        executions: &mut HashMap<Id<GenericMarker>, Running>,
+        key: Id<GenericMarker>,
+        version: i64,
+    ) -> RunningExecution {
+        let generation = self.generation.fetch_add(1, Ordering::Relaxed);
+        let token = CancellationToken::new();
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let superseded = executions.insert(
+            key,
+            Running {
+                generation,
+                version,
+                token: token.clone(),
+                stopped: stopped.clone(),
+            },
+        );
+        if let Some(superseded) = superseded {
+            tracing::debug!("cancelling superseded execution {key}");
+            superseded.token.cancel();
+        }
+
+        RunningExecution {
+            key,
+            generation,
+            token,
+            stopped,
+            registry: self.clone(),
+        }
+    }
+
+    /// Cancels the execution running under `key`.
+    ///
+    /// Returns `false` if there was nothing to cancel.
+    pub fn cancel(&self, key: Id<GenericMarker>) -> bool {
+        let Some(running) = self.executions.lock().unwrap().remove(&key) else {
+            return false;
+        };
+        running.token.cancel();
+
+        true
+    }
+
+    /// Cancels the execution running under `key` like [`cancel`](Self::cancel), leaving it to
+    /// the execution to show that it was stopped in its reply.
+    pub fn stop(&self, key: Id<GenericMarker>) {
+        if let Some(running) = self.executions.lock().unwrap().remove(&key) {
+            running.stopped.store(true, Ordering::Relaxed);
+            running.token.cancel();
+        }
+    }
+}
+
+/// An execution registered with [`RunningExecutions`], unregistered again when dropped.
+pub struct RunningExecution {
+    key: Id<GenericMarker>,
+    generation: u64,
+    token: CancellationToken,
+    stopped: Arc<AtomicBool>,
+    registry: Arc<RunningExecutions>,
+}
+
+impl RunningExecution {
+    pub fn key(&self) -> Id<GenericMarker> {
+        self.key
+    }
+
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Whether the execution was cancelled through [`RunningExecutions::stop`].
+    pub fn stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for RunningExecution {
+    fn drop(&mut self) {
+        let mut executions = self.registry.executions.lock().unwrap();
+        // a newer execution may have taken our place already
+        if executions
+            .get(&self.key)
+            .is_some_and(|running| running.generation == self.generation)
+        {
+            executions.remove(&self.key);
+        }
+    }
+}
+
+/// The paginated outputs of finished executions, keyed like [`RunningExecutions`].
+///
+/// Outputs are kept for the configured retention window, after which their pages can't be
+/// flipped through anymore.
+#[derive(Default)]
+pub struct PagedOutputs {
+    outputs: Mutex<HashMap<Id<GenericMarker>, (Instant, Paged)>>,
+}
+
+impl PagedOutputs {
+    /// Stores the output of the execution under `key`, replacing the previous one.
+    pub fn insert(&self, key: Id<GenericMarker>, paged: Paged) {
+        let mut outputs = self.outputs.lock().unwrap();
+        outputs.retain(|_, (stored, _)| stored.elapsed() < retention());
+        outputs.insert(key, (Instant::now(), paged));
+    }
+
+    pub fn remove(&self, key: Id<GenericMarker>) {
+        self.outputs.lock().unwrap().remove(&key);
+    }
+
+    /// The output stored under `key`, unless it expired.
+    pub fn get(&self, key: Id<GenericMarker>) -> Option<Paged> {
+        let outputs = self.outputs.lock().unwrap();
+        let (stored, paged) = outputs.get(&key)?;
+
+        (stored.elapsed() < retention()).then(|| paged.clone())
+    }
+}
+
+fn retention() -> Duration {
+    Duration::from_secs(CONFIG.page_retention_secs)
 }